@@ -0,0 +1,38 @@
+// Uses
+use std::{net::IpAddr, path::PathBuf};
+
+use rocket::http::Status;
+
+/// A description of a response served by a
+/// [`crate::ListingFileServer`], as passed to its
+/// [`crate::ListingFileServer::on_access`] callback.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccessEvent {
+	/// The path that was served, relative to the server root.
+	///
+	/// For an index file served in place of a listing, this is the path of the
+	/// index file.
+	pub path: PathBuf,
+	/// What was served.
+	pub kind: AccessKind,
+	/// The status of the response.
+	pub status: Status,
+	/// The size of the response body in bytes, if it's known up front.
+	///
+	/// This is [`None`] for streamed responses, such as archives and
+	/// compressed responses.
+	pub size: Option<u64>,
+	/// The IP address of the client, if known.
+	pub client_ip: Option<IpAddr>,
+}
+
+/// What kind of response an [`AccessEvent`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessKind {
+	/// A file, including an index file.
+	File,
+	/// A directory listing, in any format.
+	Listing,
+	/// A directory archive download.
+	Archive,
+}
@@ -0,0 +1,202 @@
+// Uses
+use std::{
+	collections::HashSet,
+	fs::{metadata, read_link, symlink_metadata, File},
+	io::{self, Write},
+	path::{Component, PathBuf},
+};
+
+use flate2::{write::GzEncoder, Compression};
+use rocket::{
+	http::{ContentType, Header},
+	tokio::{io::duplex, task::spawn_blocking},
+	warn_,
+	FromFormField,
+	Response,
+};
+use tar::Builder;
+use time::OffsetDateTime;
+use zip::{write::SimpleFileOptions, CompressionMethod, DateTime, ZipWriter};
+
+use crate::{encoding::attachment_disposition, reader::TreeEntry, util::BlockingWriter};
+
+/// The size of the in-memory pipe between the archive writer and the response
+/// body.
+const PIPE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The formats a directory can be downloaded as, selected with the `download`
+/// query parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromFormField)]
+pub(crate) enum ArchiveFormat {
+	/// A zip archive.
+	Zip,
+	/// A gzip-compressed tar archive, which keeps file permissions.
+	#[field(value = "tar.gz")]
+	TarGz,
+}
+
+/// Builds a response that streams an archive of the entries of `tree`, named
+/// after `name`.
+///
+/// The archive is written on a blocking thread as the response body is read,
+/// so it's never buffered in memory in its entirety. If there's no `tree`, as
+/// for `HEAD` requests, the body is left empty.
+pub(crate) fn archive_response<'r>(
+	format: ArchiveFormat,
+	tree: Option<Vec<TreeEntry>>,
+	name: &str,
+) -> Response<'r> {
+	let (reader, writer) = duplex(PIPE_BUFFER_SIZE);
+	if let Some(tree) = tree {
+		let writer = BlockingWriter::new(writer);
+		let archive_name = name.to_owned();
+		spawn_blocking(move || {
+			let result = match format {
+				ArchiveFormat::Zip => write_zip(writer, &tree),
+				ArchiveFormat::TarGz => write_tar_gz(writer, &tree),
+			};
+			if let Err(e) = result {
+				warn_!("Archive of '{}' was cut short: {}", archive_name, e);
+			}
+		});
+	}
+
+	let (content_type, extension) = match format {
+		ArchiveFormat::Zip => (ContentType::ZIP, "zip"),
+		ArchiveFormat::TarGz => (ContentType::GZIP, "tar.gz"),
+	};
+	Response::build()
+		.header(content_type)
+		.header(Header::new(
+			"Content-Disposition",
+			attachment_disposition(&format!("{name}.{extension}")),
+		))
+		.streamed_body(reader)
+		.finalize()
+}
+
+/// Writes a zip archive of the entries of `tree` to `writer`.
+///
+/// Entries that can no longer be read are skipped.
+fn write_zip<W: Write>(writer: W, tree: &[TreeEntry]) -> io::Result<()> {
+	let mut zip = ZipWriter::new_stream(writer);
+	for tree_entry in tree {
+		let path = &tree_entry.merged.paths[0];
+		let Ok(entry_metadata) = metadata(path) else {
+			continue;
+		};
+
+		let mut file_options = SimpleFileOptions::default()
+			.compression_method(CompressionMethod::Deflated)
+			.large_file(entry_metadata.len() >= u64::from(u32::MAX));
+		if let Some(modified) = entry_metadata
+			.modified()
+			.ok()
+			.and_then(|modified| zip_date_time(OffsetDateTime::from(modified)))
+		{
+			file_options = file_options.last_modified_time(modified);
+		}
+
+		if tree_entry.merged.entry.is_dir {
+			zip.add_directory(format!("{}/", tree_entry.relative), file_options)?;
+		} else {
+			let Ok(mut file) = File::open(path) else {
+				continue;
+			};
+			zip.start_file(tree_entry.relative.as_str(), file_options)?;
+			io::copy(&mut file, &mut zip)?;
+		}
+	}
+	zip.finish()?;
+	Ok(())
+}
+
+/// Writes a gzip-compressed tar archive of the entries of `tree` to `writer`.
+///
+/// Entries keep the permissions and modification times of the files they're
+/// read from, and entries that can no longer be read are skipped.
+///
+/// Symlinks whose targets are archived as well are kept as symlinks, so that
+/// they still resolve once the archive is unpacked. Every other symlink is
+/// followed, as with zip archives, since a symlink to anywhere outside of the
+/// archive would either dangle or point at whatever happens to be there on
+/// the client's machine.
+fn write_tar_gz<W: Write>(writer: W, tree: &[TreeEntry]) -> io::Result<()> {
+	let relatives = tree
+		.iter()
+		.map(|tree_entry| tree_entry.relative.as_str())
+		.collect::<HashSet<_>>();
+	let mut linked_dirs: Vec<String> = Vec::new();
+	let mut tar = Builder::new(GzEncoder::new(writer, Compression::default()));
+	for tree_entry in tree {
+		// The contents of a directory kept as a symlink are archived wherever
+		// it points
+		if linked_dirs
+			.iter()
+			.any(|prefix| tree_entry.relative.starts_with(prefix.as_str()))
+		{
+			continue;
+		}
+		let path = &tree_entry.merged.paths[0];
+		if let Some(target) = archived_link_target(tree_entry, &relatives) {
+			let Ok(link_metadata) = symlink_metadata(path) else {
+				continue;
+			};
+			let mut header = tar::Header::new_gnu();
+			header.set_metadata(&link_metadata);
+			tar.append_link(&mut header, &tree_entry.relative, target)?;
+			if tree_entry.merged.entry.is_dir {
+				linked_dirs.push(format!("{}/", tree_entry.relative));
+			}
+		} else if tree_entry.merged.entry.is_dir {
+			tar.append_dir(&tree_entry.relative, path)?;
+		} else {
+			let Ok(mut file) = File::open(path) else {
+				continue;
+			};
+			tar.append_file(&tree_entry.relative, &mut file)?;
+		}
+	}
+	tar.into_inner()?.finish()?;
+	Ok(())
+}
+
+/// Gets the target of `tree_entry`, if it's a symlink to another entry of the
+/// tree whose relative paths are `relatives`.
+///
+/// Only relative targets are considered, and they're resolved by name alone,
+/// the way they will be once the archive is unpacked.
+fn archived_link_target(tree_entry: &TreeEntry, relatives: &HashSet<&str>) -> Option<PathBuf> {
+	if !tree_entry.merged.entry.is_symlink {
+		return None;
+	}
+	let target = read_link(&tree_entry.merged.paths[0]).ok()?;
+	let mut resolved = tree_entry.relative.split('/').collect::<Vec<_>>();
+	resolved.pop();
+	for component in target.components() {
+		match component {
+			Component::CurDir => {}
+			Component::ParentDir => {
+				resolved.pop()?;
+			}
+			Component::Normal(name) => resolved.push(name.to_str()?),
+			Component::RootDir | Component::Prefix(_) => return None,
+		}
+	}
+	relatives
+		.contains(resolved.join("/").as_str())
+		.then_some(target)
+}
+
+/// Converts a time to the (UTC) zip representation, if it's representable.
+fn zip_date_time(time: OffsetDateTime) -> Option<DateTime> {
+	DateTime::from_date_and_time(
+		u16::try_from(time.year()).ok()?,
+		time.month().into(),
+		time.day(),
+		time.hour(),
+		time.minute(),
+		time.second(),
+	)
+	.ok()
+}
@@ -0,0 +1,187 @@
+// Uses
+use std::{
+	collections::hash_map::DefaultHasher,
+	hash::{Hash, Hasher},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use httpdate::{fmt_http_date, parse_http_date};
+use rocket::{
+	http::{Header, Status},
+	Request,
+	Response,
+};
+
+use crate::ListingEntry;
+
+/// A `Cache-Control` policy for responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheControl {
+	/// Responses may be stored, but must be revalidated before each use.
+	NoCache,
+	/// Responses may be reused without revalidation for the given duration,
+	/// to the second.
+	MaxAge(Duration),
+	/// Responses never change, so may be reused for as long as possible.
+	///
+	/// This is only appropriate for content that's replaced under a new name
+	/// rather than modified, such as fingerprinted assets.
+	Immutable,
+}
+
+impl CacheControl {
+	/// The maximum age used for [`CacheControl::Immutable`] responses, of one
+	/// year.
+	const IMMUTABLE_MAX_AGE: u64 = 365 * 24 * 60 * 60;
+
+	/// Sets the `Cache-Control` header of `response` according to the policy.
+	pub(crate) fn apply(self, response: &mut Response<'_>) {
+		let value = match self {
+			CacheControl::NoCache => String::from("no-cache"),
+			CacheControl::MaxAge(max_age) => format!("max-age={}", max_age.as_secs()),
+			CacheControl::Immutable => format!("max-age={}, immutable", Self::IMMUTABLE_MAX_AGE),
+		};
+		response.set_header(Header::new("Cache-Control", value));
+	}
+}
+
+/// The validators used for conditional requests of a directory listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ListingValidators {
+	/// The weak entity tag of the listing.
+	etag: String,
+	/// The latest modification time of the directory or any of its entries.
+	last_modified: SystemTime,
+}
+
+impl ListingValidators {
+	/// Computes the validators for a listing of a directory last modified at
+	/// `dir_modified`, with entries `entries`, as served in the representation
+	/// `variant`.
+	///
+	/// The directory's own modified time only changes when entries are added,
+	/// removed, or renamed, so the latest modified time of the entries is also
+	/// taken into account. The entity tag also covers the entries themselves
+	/// and `variant`, such as the format and query the listing was requested
+	/// with, so that different representations of the same directory, or the
+	/// listings of it that different clients may see, never share a tag.
+	///
+	/// Returns [`None`] if no modified times are available.
+	pub(crate) fn new<V: Hash>(
+		dir_modified: Option<SystemTime>,
+		entries: &[ListingEntry],
+		variant: &V,
+	) -> Option<Self> {
+		let last_modified = entries
+			.iter()
+			.filter_map(|entry| entry.modified)
+			.chain(dir_modified)
+			.max()?;
+		let since_epoch = last_modified.duration_since(UNIX_EPOCH).unwrap_or_default();
+		let mut hasher = DefaultHasher::new();
+		variant.hash(&mut hasher);
+		for entry in entries {
+			(&entry.name, entry.size, entry.modified).hash(&mut hasher);
+		}
+		let etag = format!(
+			"W/\"{:x}.{:x}-{:x}-{:x}\"",
+			since_epoch.as_secs(),
+			since_epoch.subsec_nanos(),
+			entries.len(),
+			hasher.finish()
+		);
+
+		Some(ListingValidators {
+			etag,
+			last_modified,
+		})
+	}
+
+	/// Checks whether the client's cached copy of the listing is still fresh,
+	/// according to the `If-None-Match` or `If-Modified-Since` headers of
+	/// `req`.
+	///
+	/// As per RFC 7232, `If-Modified-Since` is ignored if `If-None-Match` is
+	/// present.
+	pub(crate) fn is_fresh(&self, req: &Request<'_>) -> bool {
+		let headers = req.headers();
+		if let Some(if_none_match) = headers.get_one("If-None-Match") {
+			return if_none_match
+				.split(',')
+				.map(str::trim)
+				.any(|tag| tag == "*" || weak_tag(tag) == weak_tag(&self.etag));
+		}
+		if let Some(if_modified_since) = headers
+			.get_one("If-Modified-Since")
+			.and_then(|date| parse_http_date(date).ok())
+		{
+			return whole_seconds(self.last_modified) <= whole_seconds(if_modified_since);
+		}
+		false
+	}
+
+	/// Sets the `ETag` and `Last-Modified` headers on `response`.
+	pub(crate) fn apply(&self, response: &mut Response<'_>) {
+		response.set_header(Header::new("ETag", self.etag.clone()));
+		response.set_header(Header::new(
+			"Last-Modified",
+			fmt_http_date(self.last_modified),
+		));
+	}
+
+	/// Builds a `304 Not Modified` response carrying the validators.
+	pub(crate) fn not_modified<'r>(&self) -> Response<'r> {
+		let mut response = Response::build().status(Status::NotModified).finalize();
+		self.apply(&mut response);
+		response
+	}
+}
+
+/// Adds `field` to the `Vary` header of `response`, so that caches store a
+/// separate copy of it for each value of that request header.
+///
+/// Nothing is added if `response` already varies on `field`, or on
+/// everything.
+pub(crate) fn adjoin_vary(response: &mut Response<'_>, field: &str) {
+	let varies = response
+		.headers()
+		.get("Vary")
+		.flat_map(|value| value.split(','))
+		.map(str::trim)
+		.any(|existing| existing == "*" || existing.eq_ignore_ascii_case(field));
+	if !varies {
+		response.adjoin_header(Header::new("Vary", field.to_owned()));
+	}
+}
+
+/// Checks whether the client's cached copy of a file last modified at
+/// `modified` is still fresh, according to the `If-Modified-Since` header of
+/// `req`.
+///
+/// Files have no entity tags, so a request with an `If-None-Match` header is
+/// never fresh, since that header takes precedence.
+pub(crate) fn is_file_fresh(req: &Request<'_>, modified: SystemTime) -> bool {
+	let headers = req.headers();
+	if headers.contains("If-None-Match") {
+		return false;
+	}
+	headers
+		.get_one("If-Modified-Since")
+		.and_then(|date| parse_http_date(date).ok())
+		.is_some_and(|if_modified_since| {
+			whole_seconds(modified) <= whole_seconds(if_modified_since)
+		})
+}
+
+/// Strips the weakness indicator from an entity tag, for weak comparison.
+fn weak_tag(tag: &str) -> &str {
+	tag.strip_prefix("W/").unwrap_or(tag)
+}
+
+/// Gets the number of whole seconds since the UNIX epoch of `time`, which is
+/// the precision of HTTP dates.
+pub(crate) fn whole_seconds(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_secs())
+		.unwrap_or_default()
+}
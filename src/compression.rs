@@ -0,0 +1,168 @@
+// Uses
+use std::{
+	io::{self, SeekFrom, Write},
+	pin::Pin,
+	task::{Context, Poll},
+};
+
+use flate2::{write::GzEncoder, Compression};
+use rocket::{
+	http::{ContentType, Header, Method, Status},
+	tokio::{
+		self,
+		io::{duplex, AsyncRead, AsyncReadExt, AsyncSeek, AsyncWriteExt, ReadBuf},
+	},
+	Request,
+	Response,
+};
+
+use crate::caching::adjoin_vary;
+
+/// The size of the buffers used while compressing a response body.
+const BUFFER_SIZE: usize = 64 * 1024;
+/// The smallest response body, in bytes, worth compressing when its size is
+/// known up front.
+const MIN_COMPRESSED_SIZE: usize = 1024;
+
+/// Checks whether `req` accepts gzip-encoded responses, according to its
+/// `Accept-Encoding` headers.
+pub(crate) fn accepts_gzip(req: &Request<'_>) -> bool {
+	accepts_encoding(req, &["gzip", "x-gzip"])
+}
+
+/// Checks whether `req` accepts Brotli-encoded responses, according to its
+/// `Accept-Encoding` headers.
+pub(crate) fn accepts_brotli(req: &Request<'_>) -> bool {
+	accepts_encoding(req, &["br"])
+}
+
+/// Checks whether `req` accepts responses with any of the content codings
+/// `codings`, according to its `Accept-Encoding` headers.
+fn accepts_encoding(req: &Request<'_>, codings: &[&str]) -> bool {
+	req.headers()
+		.get("Accept-Encoding")
+		.flat_map(|value| value.split(','))
+		.any(|coding| {
+			let mut params = coding.split(';').map(str::trim);
+			let name = params.next().unwrap_or_default();
+			let rejected = params.any(|param| {
+				param
+					.strip_prefix("q=")
+					.and_then(|q| q.parse::<f32>().ok())
+					.is_some_and(|q| q <= 0.0)
+			});
+			!rejected
+				&& (codings
+					.iter()
+					.any(|coding| name.eq_ignore_ascii_case(coding))
+					|| name == "*")
+		})
+}
+
+/// Checks whether content of type `content_type` is textual, and so worth
+/// compressing.
+///
+/// Images, archives, and other binary formats are usually compressed
+/// already.
+pub(crate) fn is_compressible(content_type: &ContentType) -> bool {
+	let sub = content_type.sub().as_str();
+	content_type.top() == "text"
+		|| content_type.is_json()
+		|| content_type.is_xml()
+		|| content_type.is_javascript()
+		|| sub.ends_with("+xml")
+		|| sub.ends_with("+json")
+}
+
+/// Compresses the body of `response` with gzip as it's sent, if it's worth
+/// compressing.
+///
+/// Responses that are already encoded, partial, or too small are left as-is.
+/// Byte ranges of a compressed body can't be requested, since they would be
+/// served from the uncompressed file, so `Accept-Ranges` is removed from
+/// responses that are compressed.
+/// For a `HEAD` request `req`, the headers are set as they would be for
+/// `GET`, but the body, which is dropped anyway, isn't compressed. It's
+/// replaced with a [`LengthlessBody`] instead, so that no `Content-Length` is
+/// sent, the same as for `GET`.
+pub(crate) async fn compress(req: &Request<'_>, response: &mut Response<'static>) {
+	if response.status() != Status::Ok
+		|| response.headers().contains("Content-Encoding")
+		|| !response
+			.content_type()
+			.is_some_and(|ct| is_compressible(&ct))
+	{
+		return;
+	}
+	if response
+		.body_mut()
+		.size()
+		.await
+		.is_some_and(|size| size < MIN_COMPRESSED_SIZE)
+	{
+		return;
+	}
+
+	response.set_header(Header::new("Content-Encoding", "gzip"));
+	response.remove_header("Accept-Ranges");
+	adjoin_vary(response, "Accept-Encoding");
+	if req.method() == Method::Head {
+		response.set_sized_body(None, LengthlessBody);
+		return;
+	}
+
+	let mut body = response.body_mut().take();
+	let (mut writer, reader) = duplex(BUFFER_SIZE);
+	tokio::spawn(async move {
+		let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+		let mut buffer = vec![0; BUFFER_SIZE];
+		loop {
+			let read = body.read(&mut buffer).await?;
+			if read == 0 {
+				break;
+			}
+			encoder.write_all(&buffer[..read])?;
+			let compressed = encoder.get_mut();
+			if !compressed.is_empty() {
+				writer.write_all(compressed).await?;
+				compressed.clear();
+			}
+		}
+		writer.write_all(&encoder.finish()?).await?;
+		writer.shutdown().await
+	});
+
+	response.set_streamed_body(reader);
+}
+
+/// An empty body whose length can't be determined, standing in for the
+/// compressed body of a `HEAD` response.
+///
+/// Rocket replaces a streamed body with one of length 0 for `HEAD` requests,
+/// and sends that length, whereas the streamed body of a `GET` response is
+/// sent without one. A sized body whose length can't be found by seeking is
+/// sent without one instead.
+struct LengthlessBody;
+
+impl AsyncRead for LengthlessBody {
+	fn poll_read(
+		self: Pin<&mut Self>,
+		_: &mut Context<'_>,
+		_: &mut ReadBuf<'_>,
+	) -> Poll<io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+}
+
+impl AsyncSeek for LengthlessBody {
+	fn start_seek(self: Pin<&mut Self>, _: SeekFrom) -> io::Result<()> {
+		Err(io::Error::new(
+			io::ErrorKind::Unsupported,
+			"the length of a compressed body isn't known",
+		))
+	}
+
+	fn poll_complete(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<u64>> {
+		Poll::Ready(Ok(0))
+	}
+}
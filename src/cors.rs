@@ -0,0 +1,141 @@
+// Uses
+use std::time::Duration;
+
+use rocket::{
+	http::{Header, Method, Status},
+	Request,
+	Response,
+};
+
+/// A cross-origin resource sharing (CORS) policy for responses, as set with
+/// [`crate::ListingFileServer::cors`].
+///
+/// Only `GET` and `HEAD` requests are ever served, so those are the methods
+/// allowed by default.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CorsConfig {
+	/// The origins allowed to make cross-origin requests, or [`None`] for
+	/// any origin.
+	origins: Option<Vec<String>>,
+	/// The methods allowed in cross-origin requests.
+	methods: Vec<Method>,
+	/// The request headers allowed in cross-origin requests.
+	headers: Vec<String>,
+	/// How long the results of a preflight request may be cached for.
+	max_age: Option<Duration>,
+}
+
+impl CorsConfig {
+	/// Creates a policy that allows `GET` and `HEAD` requests from any origin.
+	#[must_use]
+	pub fn any_origin() -> Self {
+		CorsConfig {
+			origins: None,
+			methods: vec![Method::Get, Method::Head],
+			headers: Vec::new(),
+			max_age: None,
+		}
+	}
+
+	/// Creates a policy that allows `GET` and `HEAD` requests from the origins
+	/// `origins`, such as `https://example.com`.
+	///
+	/// Origins are compared exactly, apart from ASCII case.
+	#[must_use]
+	pub fn new<S: AsRef<str>>(origins: &[S]) -> Self {
+		CorsConfig {
+			origins: Some(
+				origins
+					.iter()
+					.map(|origin| origin.as_ref().to_owned())
+					.collect(),
+			),
+			..CorsConfig::any_origin()
+		}
+	}
+
+	/// Sets the methods allowed in cross-origin requests.
+	#[must_use]
+	pub fn methods(mut self, methods: &[Method]) -> Self {
+		self.methods = methods.to_vec();
+		self
+	}
+
+	/// Sets the request headers allowed in cross-origin requests, beyond those
+	/// that are always allowed.
+	#[must_use]
+	pub fn headers<S: AsRef<str>>(mut self, headers: &[S]) -> Self {
+		self.headers = headers
+			.iter()
+			.map(|header| header.as_ref().to_owned())
+			.collect();
+		self
+	}
+
+	/// Sets how long clients may cache the results of a preflight request for,
+	/// to the second.
+	///
+	/// The default is to leave it up to the client.
+	#[must_use]
+	pub fn max_age(mut self, max_age: Duration) -> Self {
+		self.max_age = Some(max_age);
+		self
+	}
+
+	/// Gets the value of the `Access-Control-Allow-Origin` header for `req`,
+	/// if its origin is allowed.
+	fn allowed_origin(&self, req: &Request<'_>) -> Option<String> {
+		let origin = req.headers().get_one("Origin")?;
+		match &self.origins {
+			None => Some(String::from("*")),
+			Some(origins) => origins
+				.iter()
+				.any(|allowed| allowed.eq_ignore_ascii_case(origin))
+				.then(|| origin.to_owned()),
+		}
+	}
+
+	/// Sets the CORS headers of `response` to `req`, if it's a cross-origin
+	/// request from an allowed origin.
+	pub(crate) fn apply(&self, req: &Request<'_>, response: &mut Response<'_>) {
+		if self.origins.is_some() {
+			response.adjoin_header(Header::new("Vary", "Origin"));
+		}
+		if let Some(origin) = self.allowed_origin(req) {
+			response.set_header(Header::new("Access-Control-Allow-Origin", origin));
+		}
+	}
+
+	/// Builds the response to the `OPTIONS` request `req`, which lists the
+	/// methods served, and answers its preflight if it's from an allowed
+	/// origin.
+	pub(crate) fn preflight<'r>(&self, req: &Request<'_>) -> Response<'r> {
+		let methods = self
+			.methods
+			.iter()
+			.map(|&method| method.as_str())
+			.collect::<Vec<_>>()
+			.join(", ");
+		let mut response = Response::build()
+			.status(Status::NoContent)
+			.header(Header::new("Allow", "GET, HEAD, OPTIONS"))
+			.finalize();
+		self.apply(req, &mut response);
+		if response.headers().contains("Access-Control-Allow-Origin") {
+			response.set_header(Header::new("Access-Control-Allow-Methods", methods));
+			if !self.headers.is_empty() {
+				response.set_header(Header::new(
+					"Access-Control-Allow-Headers",
+					self.headers.join(", "),
+				));
+			}
+			if let Some(max_age) = self.max_age {
+				response.set_header(Header::new(
+					"Access-Control-Max-Age",
+					max_age.as_secs().to_string(),
+				));
+			}
+		}
+		response
+	}
+}
@@ -0,0 +1,15 @@
+// Uses
+use crate::reader::TreeEntry;
+
+/// Sums the sizes of the files of `tree`.
+///
+/// Each file is only counted once, however many roots have it, since the
+/// tree is merged with the first root winning. The tree should be walked
+/// without following symlinks, so that no file is counted twice through a
+/// link to it.
+pub(crate) fn dir_size(tree: &[TreeEntry]) -> u64 {
+	tree.iter()
+		.filter(|tree_entry| !tree_entry.merged.entry.is_dir)
+		.filter_map(|tree_entry| tree_entry.merged.entry.size)
+		.sum()
+}
@@ -0,0 +1,56 @@
+// Uses
+use std::path::{Component, Path, PathBuf};
+
+use rocket::{
+	figment::{
+		providers::{Format, Toml},
+		Figment,
+	},
+	serde::Deserialize,
+	tokio::fs::read_to_string,
+};
+
+/// The name of the optional per-directory configuration file.
+pub(crate) const DIRECTORY_CONFIG_FILE: &str = ".listing.toml";
+
+/// The settings read from a directory's [`DIRECTORY_CONFIG_FILE`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub(crate) struct DirectoryConfig {
+	/// The filename of the index file to serve for the directory, tried before
+	/// the server-wide index files.
+	pub index: Option<String>,
+	/// The title to show for the directory's listing.
+	pub title: Option<String>,
+}
+
+impl DirectoryConfig {
+	/// Reads the configuration from the first of `directories` that has a
+	/// valid configuration file.
+	///
+	/// Missing, unreadable, or malformed configuration files are ignored, as
+	/// is an index filename that isn't a plain name within the directory.
+	pub(crate) async fn read(directories: &[PathBuf]) -> Self {
+		for directory in directories {
+			let Ok(contents) = read_to_string(directory.join(DIRECTORY_CONFIG_FILE)).await else {
+				continue;
+			};
+			let Ok(mut config) = Figment::from(Toml::string(&contents)).extract::<Self>() else {
+				continue;
+			};
+			config.index = config.index.filter(|index| is_plain_name(index));
+			return config;
+		}
+		Self::default()
+	}
+}
+
+/// Checks whether `name` names an entry directly within a directory, without
+/// any other path components.
+fn is_plain_name(name: &str) -> bool {
+	let mut components = Path::new(name).components();
+	matches!(
+		(components.next(), components.next()),
+		(Some(Component::Normal(_)), None)
+	)
+}
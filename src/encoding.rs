@@ -0,0 +1,40 @@
+// Uses
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+/// The set of characters percent-encoded in a path segment.
+///
+/// This is everything except the unreserved characters of RFC 3986, which is
+/// stricter than necessary but guarantees the result is safe to use in a
+/// relative link (a segment containing `:` could otherwise be mistaken for a
+/// scheme, for instance).
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+	.remove(b'-')
+	.remove(b'.')
+	.remove(b'_')
+	.remove(b'~');
+
+/// Percent-encodes `segment` for use as a single segment of a URL path.
+pub(crate) fn encode_path_segment(segment: &str) -> String {
+	utf8_percent_encode(segment, PATH_SEGMENT).to_string()
+}
+
+/// Builds a `Content-Disposition` header value that prompts the client to
+/// download the response as `filename`.
+///
+/// Clients that support RFC 6266 get the exact (UTF-8) filename, and others get
+/// an ASCII approximation of it.
+pub(crate) fn attachment_disposition(filename: &str) -> String {
+	let fallback = filename
+		.chars()
+		.map(|c| match c {
+			'"' | '\\' => '_',
+			c if c.is_ascii_graphic() || c == ' ' => c,
+			_ => '_',
+		})
+		.collect::<String>();
+	format!(
+		"attachment; filename=\"{}\"; filename*=UTF-8''{}",
+		fallback,
+		utf8_percent_encode(filename, PATH_SEGMENT)
+	)
+}
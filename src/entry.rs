@@ -0,0 +1,225 @@
+// Uses
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rocket::{
+	http::MediaType,
+	serde::{Serialize, Serializer},
+};
+
+/// A single entry within a directory listing, as passed to the template
+/// renderer.
+///
+/// The metadata for each entry is gathered with one `stat` call per entry, so
+/// listing very large directories has a proportional cost.
+///
+/// When serialized, the `modified` time is represented as a number of seconds
+/// since the UNIX epoch.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ListingEntry {
+	/// The name of the entry, for display.
+	///
+	/// Directory names have the [`crate::ListingFileServer::dir_suffix`]
+	/// appended, which is `/` by default.
+	pub name: String,
+	/// The name shown for the entry.
+	///
+	/// This is the label given by the
+	/// [`crate::ListingFileServer::display_name_fn`], if any, or the same as
+	/// [`ListingEntry::name`] otherwise. Directory labels also have the
+	/// [`crate::ListingFileServer::dir_suffix`] appended.
+	pub display_name: String,
+	/// The name of the entry, percent-encoded for use in relative links.
+	///
+	/// Directory links have a trailing `/` appended.
+	pub href: String,
+	/// Whether the entry is a directory.
+	pub is_dir: bool,
+	/// The size of the entry in bytes.
+	///
+	/// This is [`None`] for entries whose metadata couldn't be read, and for
+	/// directories unless enabled with
+	/// [`crate::ListingFileServer::compute_dir_sizes`], in which case it's the
+	/// total size of the files beneath the directory.
+	pub size: Option<u64>,
+	/// The size of the entry in human-readable units, such as `1.2 GiB`.
+	///
+	/// This is set whenever [`ListingEntry::size`] is, formatted with the
+	/// [`crate::ListingFileServer::size_units`].
+	pub human_size: Option<String>,
+	/// The last-modified time of the entry.
+	///
+	/// This is [`None`] if the platform doesn't support modified times, or if
+	/// the entry's metadata couldn't be read.
+	#[serde(serialize_with = "serialize_timestamp")]
+	pub modified: Option<SystemTime>,
+	/// The content type of the entry, guessed from its extension.
+	///
+	/// This is [`None`] for directories, and for files with an unknown
+	/// extension.
+	pub content_type: Option<String>,
+	/// The broad kind of the entry, for picking an icon.
+	pub kind: EntryKind,
+	/// Whether the entry is a symlink.
+	///
+	/// Symlinks that are followed are otherwise described by their targets,
+	/// including [`ListingEntry::is_dir`]. Broken symlinks are still listed,
+	/// without metadata.
+	pub is_symlink: bool,
+	/// The target of the entry, if it's a symlink.
+	///
+	/// This is only read if enabled with
+	/// [`crate::ListingFileServer::show_symlink_targets`].
+	pub symlink_target: Option<String>,
+	/// Whether the entry is hidden, so renderers may want to de-emphasize it.
+	///
+	/// This is set for dotfiles, which are only listed if
+	/// [`crate::ListingFileServer::hide_dotfiles_in_listing`] is disabled, and
+	/// for entries matching the
+	/// [`crate::ListingFileServer::hidden_patterns`].
+	pub is_hidden: bool,
+	/// The Unix permission bits of the entry, such as `0o755`.
+	///
+	/// This is [`None`] on other platforms, and for entries whose metadata
+	/// couldn't be read.
+	pub mode: Option<u32>,
+	/// Whether the server is likely able to serve the entry.
+	///
+	/// This is a best-effort guess from the entry's permission bits and the
+	/// user the server runs as, rather than an attempt to open it. On other
+	/// platforms than Unix, this is always set for entries whose metadata
+	/// could be read. Entries whose metadata couldn't be read are never
+	/// readable.
+	pub is_readable: bool,
+}
+
+/// The broad kind of a directory listing entry, guessed from its extension.
+///
+/// When serialized, this is the lowercase variant name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum EntryKind {
+	/// A directory.
+	Directory,
+	/// An image file.
+	Image,
+	/// An audio file.
+	Audio,
+	/// A video file.
+	Video,
+	/// A text file.
+	Text,
+	/// An archive file, such as a ZIP or tarball.
+	Archive,
+	/// Any other file, including files with an unknown extension.
+	File,
+}
+
+impl EntryKind {
+	/// Guesses the kind of a file from its content type.
+	fn from_content_type(content_type: Option<&MediaType>) -> Self {
+		let Some(content_type) = content_type else {
+			return EntryKind::File;
+		};
+		match content_type.top().as_str() {
+			"image" => EntryKind::Image,
+			"audio" => EntryKind::Audio,
+			"video" => EntryKind::Video,
+			"text" => EntryKind::Text,
+			_ if content_type.is_zip() || content_type.is_gzip() || content_type.is_tar() => {
+				EntryKind::Archive
+			}
+			_ => EntryKind::File,
+		}
+	}
+}
+
+/// The units that entry sizes are formatted in for display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SizeUnits {
+	/// Multiples of 1024 bytes, such as `KiB` and `MiB`.
+	#[default]
+	Binary,
+	/// Multiples of 1000 bytes, such as `kB` and `MB`.
+	Decimal,
+}
+
+impl SizeUnits {
+	/// Formats `size`, in bytes, in the units.
+	///
+	/// Sizes below 10 of a unit are shown to one decimal place, such as
+	/// `1.2 GiB`, and larger ones are rounded to a whole number, such as
+	/// `340 KiB`. Sizes below a kilobyte are shown in bytes.
+	pub(crate) fn format(self, size: u64) -> String {
+		let (base, units) = match self {
+			SizeUnits::Binary => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"]),
+			SizeUnits::Decimal => (1000.0, ["B", "kB", "MB", "GB", "TB", "PB", "EB"]),
+		};
+		let mut value = size as f64;
+		let mut unit = 0;
+		// Move up a unit before rounding could reach the base, such as `1024 KiB`
+		while value >= base - 0.5 && unit < units.len() - 1 {
+			value /= base;
+			unit += 1;
+		}
+		if unit == 0 {
+			format!("{size} {}", units[0])
+		} else if value < 9.95 {
+			format!("{value:.1} {}", units[unit])
+		} else {
+			format!("{value:.0} {}", units[unit])
+		}
+	}
+}
+
+/// Which entries are shown in directory listings, by whether they're
+/// directories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EntryFilter {
+	/// Show every entry.
+	#[default]
+	All,
+	/// Show only directories.
+	DirectoriesOnly,
+	/// Show only files.
+	FilesOnly,
+}
+
+impl EntryFilter {
+	/// Checks whether `entry` is shown by the filter.
+	pub(crate) fn shows(self, entry: &ListingEntry) -> bool {
+		match self {
+			EntryFilter::All => true,
+			EntryFilter::DirectoriesOnly => entry.is_dir,
+			EntryFilter::FilesOnly => !entry.is_dir,
+		}
+	}
+}
+
+/// Guesses the content type and kind of the entry named `name` from its
+/// extension.
+pub(crate) fn guess_type(name: &str, is_dir: bool) -> (Option<String>, EntryKind) {
+	if is_dir {
+		return (None, EntryKind::Directory);
+	}
+	let content_type = name
+		.rsplit_once('.')
+		.and_then(|(_, extension)| MediaType::from_extension(extension));
+	(
+		content_type.as_ref().map(ToString::to_string),
+		EntryKind::from_content_type(content_type.as_ref()),
+	)
+}
+
+/// Serializes an optional time as the number of seconds since the UNIX epoch.
+///
+/// Times from before the epoch are serialized as [`None`].
+#[allow(clippy::ref_option)] // The signature is dictated by `serialize_with`
+fn serialize_timestamp<S: Serializer>(
+	time: &Option<SystemTime>,
+	serializer: S,
+) -> Result<S::Ok, S::Error> {
+	time.and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+		.map(|duration| duration.as_secs())
+		.serialize(serializer)
+}
@@ -0,0 +1,96 @@
+// Uses
+use std::{fmt::Write, time::SystemTime};
+
+use rocket::{http::ContentType, FromFormField};
+use time::OffsetDateTime;
+
+use crate::{html::escape_html, Listing};
+
+/// The feed formats a directory can be served as.
+///
+/// This is parsed from the `feed` query parameter of a listing request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, FromFormField)]
+pub(crate) enum FeedFormat {
+	/// An Atom feed.
+	Atom,
+}
+
+impl FeedFormat {
+	/// The content type of the feed format.
+	pub(crate) fn content_type(self) -> ContentType {
+		match self {
+			FeedFormat::Atom => ContentType::new("application", "atom+xml"),
+		}
+	}
+}
+
+/// Renders the files of `listing` as an Atom feed, newest first.
+///
+/// Links are made absolute by prefixing them with `origin`, such as
+/// `https://example.com`, which may be empty if it isn't known.
+pub(crate) fn render_atom(listing: &Listing, origin: &str) -> String {
+	let directory_href = &listing.url;
+	let directory_url = escape_html(&format!("{origin}{directory_href}"));
+	let title = listing.title.as_deref().map_or_else(
+		|| escape_html(&format!("Index of {}", listing.directory)),
+		escape_html,
+	);
+
+	let mut files = listing
+		.entries
+		.iter()
+		.filter(|entry| !entry.is_dir)
+		.collect::<Vec<_>>();
+	files.sort_by(|a, b| {
+		b.modified
+			.cmp(&a.modified)
+			.then_with(|| a.name.cmp(&b.name))
+	});
+	let updated = files
+		.iter()
+		.find_map(|entry| entry.modified)
+		.unwrap_or_else(SystemTime::now);
+
+	let mut feed = String::new();
+	feed.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+	feed.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+	let _ = writeln!(feed, "<id>{directory_url}</id>");
+	let _ = writeln!(feed, "<title>{title}</title>");
+	let _ = writeln!(feed, "<updated>{}</updated>", format_timestamp(updated));
+	let _ = writeln!(feed, "<link href=\"{directory_url}\"/>");
+	for file in files {
+		let url = escape_html(&format!("{origin}{directory_href}{}", file.href));
+		feed.push_str("<entry>\n");
+		let _ = writeln!(feed, "<id>{url}</id>");
+		let _ = writeln!(feed, "<title>{}</title>", escape_html(&file.display_name));
+		let _ = writeln!(
+			feed,
+			"<updated>{}</updated>",
+			format_timestamp(file.modified.unwrap_or(updated))
+		);
+		let _ = write!(feed, "<link href=\"{url}\"");
+		if let Some(content_type) = &file.content_type {
+			let _ = write!(feed, " type=\"{}\"", escape_html(content_type));
+		}
+		if let Some(size) = file.size {
+			let _ = write!(feed, " length=\"{size}\"");
+		}
+		feed.push_str("/>\n</entry>\n");
+	}
+	feed.push_str("</feed>\n");
+	feed
+}
+
+/// Formats `time` as an RFC 3339 UTC timestamp, to the second.
+fn format_timestamp(time: SystemTime) -> String {
+	let time = OffsetDateTime::from(time);
+	format!(
+		"{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+		time.year(),
+		u8::from(time.month()),
+		time.day(),
+		time.hour(),
+		time.minute(),
+		time.second()
+	)
+}
@@ -0,0 +1,116 @@
+// Uses
+use rocket::{
+	http::{Accept, MediaType},
+	FromFormField,
+};
+
+/// The formats a directory listing can be served in.
+///
+/// This is also parsed from the `format` query parameter of a listing request,
+/// as the lowercase variant name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, FromFormField)]
+pub enum ListingFormat {
+	/// An HTML page, from the template renderer or the built-in one.
+	#[default]
+	Html,
+	/// JSON, if enabled with [`crate::ListingFileServer::enable_json`].
+	Json,
+	/// Plain text in the style of `ls -l`, if enabled with
+	/// [`crate::ListingFileServer::enable_text`].
+	Text,
+	/// An Atom feed of the directory's files, if enabled with
+	/// [`crate::ListingFileServer::enable_feed`].
+	Atom,
+	/// An M3U playlist of the directory's media files, if enabled with
+	/// [`crate::ListingFileServer::enable_playlist`].
+	M3u,
+}
+
+impl ListingFormat {
+	/// The media types that select the format in an `Accept` header.
+	fn media_types(self) -> &'static [(&'static str, &'static str)] {
+		match self {
+			ListingFormat::Html => &[("text", "html"), ("application", "xhtml+xml")],
+			ListingFormat::Json => &[("application", "json")],
+			ListingFormat::Text => &[("text", "plain")],
+			ListingFormat::Atom => &[("application", "atom+xml")],
+			ListingFormat::M3u => &[("audio", "x-mpegurl"), ("audio", "mpegurl")],
+		}
+	}
+}
+
+/// The quality, specificity, and negated position in the header of the media
+/// range that a format was matched by, in order of precedence.
+type Rank = (f32, u8, isize);
+
+/// Picks the format of `formats` that `accept` prefers, or `default` if it
+/// prefers none of them in particular.
+///
+/// Each format is weighted by the quality of the most specific media range in
+/// `accept` that matches it, as per RFC 7231. Of the formats with the highest
+/// quality, one named by a more specific range is preferred, then one named
+/// earlier in the header. If the preferred format is only matched by `*/*`,
+/// `default` is picked instead, as long as it's acceptable. Formats with a
+/// quality of 0 are never picked, unless nothing is acceptable at all, in which
+/// case `default` is used anyway.
+pub(crate) fn negotiate(
+	accept: Option<&Accept>,
+	formats: &[ListingFormat],
+	default: ListingFormat,
+) -> ListingFormat {
+	let Some(accept) = accept else {
+		return default;
+	};
+	let acceptable = |format| rank(accept, format).filter(|rank| rank.0 > 0.0);
+	let mut best: Option<(ListingFormat, Rank)> = None;
+	for &format in formats {
+		let Some(rank) = acceptable(format) else {
+			continue;
+		};
+		if best.is_none_or(|(_, best_rank)| rank > best_rank) {
+			best = Some((format, rank));
+		}
+	}
+	match best {
+		Some((format, (_, specificity, _))) if specificity > 0 || acceptable(default).is_none() => {
+			format
+		}
+		_ => default,
+	}
+}
+
+/// Ranks `format` by the most specific media range of `accept` that matches
+/// it.
+///
+/// Returns [`None`] if no range matches it.
+fn rank(accept: &Accept, format: ListingFormat) -> Option<Rank> {
+	let mut best: Option<Rank> = None;
+	for (position, range) in accept.iter().enumerate() {
+		let media_type = range.media_type();
+		let Some(specificity) = format
+			.media_types()
+			.iter()
+			.filter_map(|&(top, sub)| specificity(media_type, top, sub))
+			.max()
+		else {
+			continue;
+		};
+		if best.is_none_or(|(_, best_specificity, _)| specificity > best_specificity) {
+			best = Some((range.weight_or(1.0), specificity, -(position as isize)));
+		}
+	}
+	best
+}
+
+/// Gets how specifically `range` matches the media type `top`/`sub`, from 0
+/// for `*/*` to 2 for an exact match, or [`None`] if it doesn't match.
+fn specificity(range: &MediaType, top: &str, sub: &str) -> Option<u8> {
+	let top_matches = range.top() == top;
+	let sub_matches = range.sub() == sub;
+	match (range.top() == "*", range.sub() == "*") {
+		(true, true) => Some(0),
+		(false, true) if top_matches => Some(1),
+		(false, false) if top_matches && sub_matches => Some(2),
+		_ => None,
+	}
+}
@@ -0,0 +1,381 @@
+// Uses
+use std::{fmt::Write, time::SystemTime};
+
+use time::OffsetDateTime;
+
+use crate::{Listing, ListingEntry, Pagination, SortBy, SortOrder};
+
+/// The stylesheet used by the built-in listing page.
+const DEFAULT_STYLE: &str = "
+body {
+	margin: 2em auto;
+	max-width: 60em;
+	padding: 0 1em;
+	font-family: system-ui, sans-serif;
+	color: #222;
+	background: #fff;
+}
+h1 {
+	font-size: 1.4em;
+	font-weight: normal;
+	word-break: break-all;
+}
+table {
+	width: 100%;
+	border-collapse: collapse;
+}
+th, td {
+	padding: 0.3em 0.6em;
+	text-align: left;
+	white-space: nowrap;
+}
+th {
+	border-bottom: 1px solid #ccc;
+}
+tbody tr:hover {
+	background: #f2f2f2;
+}
+td:first-child {
+	width: 100%;
+	white-space: normal;
+	word-break: break-all;
+}
+.size {
+	text-align: right;
+}
+.empty {
+	color: #777;
+	font-style: italic;
+}
+.hidden {
+	opacity: 0.6;
+}
+.unreadable {
+	color: #777;
+}
+.group th {
+	padding-top: 1em;
+}
+.readme {
+	padding: 1em;
+	border: 1px solid #ccc;
+	white-space: pre-wrap;
+	word-break: break-word;
+}
+nav {
+	margin-top: 1em;
+	text-align: center;
+}
+nav a {
+	margin: 0 0.6em;
+}
+a {
+	color: #0550ae;
+	text-decoration: none;
+}
+a:hover {
+	text-decoration: underline;
+}
+@media (prefers-color-scheme: dark) {
+	body {
+		color: #ddd;
+		background: #1a1a1a;
+	}
+	th {
+		border-bottom-color: #444;
+	}
+	.readme {
+		border-color: #444;
+	}
+	tbody tr:hover {
+		background: #262626;
+	}
+	a {
+		color: #6cb6ff;
+	}
+}
+";
+
+/// Renders `listing` as a self-contained HTML page, with any `inline_css`
+/// added after the default style.
+pub(crate) fn render_listing(listing: &Listing, inline_css: Option<&str>) -> String {
+	let mut html = render_head(listing, true, inline_css);
+	if let Some(groups) = &listing.groups {
+		for (extension, entries) in groups {
+			html.push_str(&render_group_row(extension));
+			for entry in entries {
+				html.push_str(&render_row(entry));
+			}
+		}
+	} else {
+		for entry in &listing.entries {
+			html.push_str(&render_row(entry));
+		}
+	}
+	html.push_str(&render_tail(listing));
+	html
+}
+
+/// Renders the start of the page for `listing`, up to its first entry.
+///
+/// The column headers only link to other sort orders if `sortable` is set.
+/// Any `inline_css` is added after the default style.
+pub(crate) fn render_head(listing: &Listing, sortable: bool, inline_css: Option<&str>) -> String {
+	let directory = escape_html(&listing.directory);
+
+	let mut html = String::new();
+	html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+	html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+	// Resolve relative links against the directory, even without a trailing slash
+	if !listing.request_path.ends_with('/') {
+		let _ = writeln!(html, "<base href=\"{}\">", escape_html(&listing.url));
+	}
+	let title = listing
+		.title
+		.as_deref()
+		.map_or_else(|| format!("Index of {directory}"), escape_html);
+	let _ = writeln!(html, "<title>{title}</title>");
+	// Keep the CSS from closing the `<style>` element early
+	let inline_css = inline_css.unwrap_or_default().replace("</", "<\\/");
+	let _ = writeln!(html, "<style>{DEFAULT_STYLE}{inline_css}</style>");
+	html.push_str("</head>\n<body>\n");
+	if let Some(title) = &listing.title {
+		let _ = write!(html, "<h1>{}</h1>\n<p>", escape_html(title));
+	} else {
+		html.push_str("<h1>Index of ");
+	}
+	for (label, href) in &listing.breadcrumbs {
+		let separator = if label == "/" { "" } else { "/" };
+		let _ = write!(
+			html,
+			"<a href=\"{}\">{}</a>{separator}",
+			escape_html(href),
+			escape_html(label)
+		);
+	}
+	html.push_str(if listing.title.is_some() {
+		"</p>\n"
+	} else {
+		"</h1>\n"
+	});
+	if let Some(search) = &listing.search {
+		let _ = writeln!(
+			html,
+			"<p>Search results for &quot;{}&quot;</p>",
+			escape_html(search)
+		);
+	}
+	if listing.recent.is_some() {
+		html.push_str("<p>Most recently modified files</p>\n");
+	}
+	if let Some(readme) = &listing.readme {
+		let _ = writeln!(html, "<pre class=\"readme\">{}</pre>", escape_html(readme));
+	}
+	html.push_str("<table>\n<thead>\n<tr>");
+	for (label, by, class) in [
+		("Name", SortBy::Name, ""),
+		("Size", SortBy::Size, " class=\"size\""),
+		("Modified", SortBy::Modified, ""),
+	] {
+		if sortable {
+			let _ = write!(html, "<th{class}>{}</th>", sort_header(label, by, listing));
+		} else {
+			let _ = write!(html, "<th{class}>{label}</th>");
+		}
+	}
+	html.push_str("</tr>\n</thead>\n<tbody>\n");
+	html
+}
+
+/// Renders the table row for `entry`.
+///
+/// Entries that the server likely can't serve are shown without a link.
+pub(crate) fn render_row(entry: &ListingEntry) -> String {
+	let classes = [
+		entry.is_hidden.then_some("hidden"),
+		(!entry.is_readable).then_some("unreadable"),
+	]
+	.into_iter()
+	.flatten()
+	.collect::<Vec<_>>();
+	let class = if classes.is_empty() {
+		String::new()
+	} else {
+		format!(" class=\"{}\"", classes.join(" "))
+	};
+	let name = if entry.is_readable {
+		format!(
+			"<a href=\"{}\">{}</a>",
+			escape_html(&entry.href),
+			escape_html(&entry.display_name)
+		)
+	} else {
+		escape_html(&entry.display_name)
+	};
+	format!(
+		"<tr{class}><td>{name}</td><td class=\"size\">{}</td><td>{}</td></tr>\n",
+		escape_html(entry.human_size.as_deref().unwrap_or_default()),
+		entry.modified.map(format_time).unwrap_or_default(),
+	)
+}
+
+/// Renders the heading row for the group of entries with the extension
+/// `extension`, as grouped in [`Listing::groups`].
+fn render_group_row(extension: &str) -> String {
+	let label = match extension {
+		"/" => String::from("Directories"),
+		"" => String::from("No extension"),
+		extension => format!(".{}", escape_html(extension)),
+	};
+	format!("<tr class=\"group\"><th colspan=\"3\">{label}</th></tr>\n")
+}
+
+/// Renders the end of the page for `listing`, after its last entry.
+pub(crate) fn render_tail(listing: &Listing) -> String {
+	let mut html = String::new();
+	if listing.empty {
+		let message = if listing.search.is_some() {
+			"No matching entries."
+		} else if listing.recent.is_some() {
+			"No files found."
+		} else {
+			"This directory is empty."
+		};
+		let _ = writeln!(
+			html,
+			"<tr><td colspan=\"3\" class=\"empty\">{message}</td></tr>"
+		);
+	}
+	html.push_str("</tbody>\n</table>\n");
+	if listing.truncated {
+		html.push_str("<p>Some entries aren't shown.</p>\n");
+	}
+	if let Some(pagination) = listing
+		.pagination
+		.filter(|pagination| pagination.page_count > 1)
+	{
+		html.push_str(&page_links(listing, pagination));
+	}
+	html.push_str("</body>\n</html>\n");
+	html
+}
+
+/// Builds a column header that links to the listing sorted by `by`.
+///
+/// If the listing is already sorted by `by`, the link reverses the order and
+/// the header is marked with an arrow.
+fn sort_header(label: &str, by: SortBy, listing: &Listing) -> String {
+	let (order, arrow) = match (by == listing.sort_by, listing.sort_order) {
+		(true, SortOrder::Ascending) => (SortOrder::Descending, " \u{25b2}"),
+		(true, SortOrder::Descending) => (SortOrder::Ascending, " \u{25bc}"),
+		(false, _) => (SortOrder::Ascending, ""),
+	};
+	let mut query = format!(
+		"{}{}{}",
+		query_base(listing),
+		sort_query(by, order),
+		carried_query(listing)
+	);
+	if let Some(pagination) = listing.pagination {
+		let _ = write!(query, "&amp;per_page={}", pagination.per_page);
+	}
+	format!("<a href=\"{query}\">{label}</a>{arrow}")
+}
+
+/// Builds the navigation links between the pages of a paginated listing.
+fn page_links(listing: &Listing, pagination: Pagination) -> String {
+	let page_href = |page: usize| {
+		format!(
+			"{}{}{}&amp;page={page}&amp;per_page={}",
+			query_base(listing),
+			sort_query(listing.sort_by, listing.sort_order),
+			carried_query(listing),
+			pagination.per_page
+		)
+	};
+	let mut nav = String::from("<nav>");
+	if pagination.page > 1 {
+		let previous = pagination.page.min(pagination.page_count + 1) - 1;
+		let _ = write!(
+			nav,
+			"<a href=\"{}\">&larr; Previous</a>",
+			page_href(previous)
+		);
+	}
+	let _ = write!(nav, "Page {} of {}", pagination.page, pagination.page_count);
+	if pagination.page < pagination.page_count {
+		let _ = write!(
+			nav,
+			"<a href=\"{}\">Next &rarr;</a>",
+			page_href(pagination.page + 1)
+		);
+	}
+	nav.push_str("</nav>\n");
+	nav
+}
+
+/// Gets the path that links changing only the query of `listing` start with.
+///
+/// This is empty unless the listing has a `<base>` element, which links that
+/// are only a query would otherwise resolve against.
+fn query_base(listing: &Listing) -> &str {
+	if listing.request_path.ends_with('/') {
+		""
+	} else {
+		listing.url.trim_end_matches('/')
+	}
+}
+
+/// Gets the parameters of the query of `listing` that links re-sorting or
+/// paging through it keep, escaped and with a leading `&amp;`, if there are
+/// any.
+fn carried_query(listing: &Listing) -> String {
+	if listing.link_query.is_empty() {
+		String::new()
+	} else {
+		format!("&amp;{}", escape_html(&listing.link_query))
+	}
+}
+
+/// Builds the query string that sorts a listing by `by` in the order `order`.
+fn sort_query(by: SortBy, order: SortOrder) -> String {
+	let by = match by {
+		SortBy::Name => "name",
+		SortBy::Size => "size",
+		SortBy::Modified => "modified",
+	};
+	let order = match order {
+		SortOrder::Ascending => "asc",
+		SortOrder::Descending => "desc",
+	};
+	format!("?sort={by}&amp;order={order}")
+}
+
+/// Formats `time` as a UTC date and time, to the minute.
+pub(crate) fn format_time(time: SystemTime) -> String {
+	let time = OffsetDateTime::from(time);
+	format!(
+		"{:04}-{:02}-{:02} {:02}:{:02}",
+		time.year(),
+		u8::from(time.month()),
+		time.day(),
+		time.hour(),
+		time.minute()
+	)
+}
+
+/// Escapes the characters in `text` that are special in HTML.
+pub(crate) fn escape_html(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+	for c in text.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&#39;"),
+			c => escaped.push(c),
+		}
+	}
+	escaped
+}
@@ -1,5 +1,12 @@
 //! A library that provides a `ListingFileServer` struct that shows directory
 //! listings for directory requests.
+//!
+//! # Features
+//!
+//! - `collation`: Sorts names with a locale's collation rules, if enabled with
+//!   `ListingFileServer::collation`.
+//! - `image`: Serves thumbnails of images, if enabled with
+//!   `ListingFileServer::enable_thumbnails`.
 
 // Linting rules
 #![warn(
@@ -24,12 +31,48 @@
 	clippy::doc_markdown,
 	clippy::module_name_repetitions,
 	clippy::similar_names,
+	clippy::struct_excessive_bools,
+	clippy::tabs_in_doc_comments,
 	clippy::too_many_lines,
 	clippy::unnecessary_wraps
 )]
 
 // Modules
+mod access;
+mod archive;
+mod caching;
+mod compression;
+mod cors;
+mod dir_size;
+mod directory_config;
+mod encoding;
+mod entry;
+mod feed;
+mod format;
+mod html;
+mod listing;
+mod playlist;
+mod range;
+mod reader;
+mod render_cache;
+mod search;
 mod server;
+mod sort;
+mod text;
+#[cfg(feature = "image")]
+mod thumbnail;
+mod trailing_slash;
+mod util;
 
 // Exports
-pub use self::server::ListingFileServer;
+pub use self::{
+	access::{AccessEvent, AccessKind},
+	caching::CacheControl,
+	cors::CorsConfig,
+	entry::{EntryFilter, EntryKind, ListingEntry, SizeUnits},
+	format::ListingFormat,
+	listing::{Listing, ListingSummary, Pagination},
+	server::{list_directory, ListingFileServer, ListingIterator},
+	sort::{SortBy, SortOrder},
+	trailing_slash::TrailingSlash,
+};
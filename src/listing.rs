@@ -0,0 +1,271 @@
+// Uses
+use std::{collections::BTreeMap, path::Path};
+
+use rocket::serde::Serialize;
+
+use crate::{encoding::encode_path_segment, ListingEntry, SortBy, SortOrder};
+
+/// A directory listing, as passed to the template renderer.
+///
+/// This is also the shape of the JSON listing, if enabled with
+/// [`crate::ListingFileServer::enable_json`]:
+///
+/// ```json
+/// {
+///   "directory": "/docs/",
+///   "url": "/docs/",
+///   "request_path": "/docs/",
+///   "mount_base": "/",
+///   "link_query": "",
+///   "breadcrumbs": [["/", "/"], ["docs", "/docs/"]],
+///   "segments": ["docs"],
+///   "entries": [
+///     {
+///       "name": "images/", "display_name": "images/", "href": "images/",
+///       "is_dir": true, "size": null, "human_size": null, "modified": 1633046400,
+///       "content_type": null, "kind": "directory", "is_symlink": false,
+///       "symlink_target": null, "is_hidden": false, "mode": 493, "is_readable": true
+///     },
+///     {
+///       "name": "read me.txt", "display_name": "read me.txt", "href": "read%20me.txt",
+///       "is_dir": false, "size": 1024, "human_size": "1.0 KiB", "modified": 1633046400,
+///       "content_type": "text/plain; charset=utf-8", "kind": "text",
+///       "is_symlink": false, "symlink_target": null, "is_hidden": false, "mode": 420,
+///       "is_readable": true
+///     }
+///   ],
+///   "sort_by": "name",
+///   "sort_order": "ascending",
+///   "pagination": null,
+///   "groups": null,
+///   "title": null,
+///   "search": null,
+///   "recent": null,
+///   "readme": null,
+///   "truncated": false,
+///   "empty": false,
+///   "summary": {"files": 1, "directories": 1, "total_size": 1024}
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Listing {
+	/// The path of the directory being listed, relative to the server root.
+	///
+	/// This always begins and ends with a `/`.
+	pub directory: String,
+	/// The percent-encoded URL path of the directory being listed, as seen by
+	/// clients.
+	///
+	/// Unlike [`Listing::directory`], this includes the path the server is
+	/// mounted at, or the [`crate::ListingFileServer::url_prefix`] if set, so
+	/// it can be used to build absolute links.
+	pub url: String,
+	/// The URL path of the request, exactly as the client sent it.
+	///
+	/// This is usually the same as [`Listing::url`], but it's taken from the
+	/// request itself rather than being rebuilt from [`Listing::directory`], so
+	/// it's unaffected by the [`crate::ListingFileServer::url_prefix`] and
+	/// lacks a trailing `/` if the client left it out. It's still
+	/// percent-encoded.
+	pub request_path: String,
+	/// The path the server is mounted at, such as `/` or `/files`, as given to
+	/// [`rocket::Rocket::mount`].
+	///
+	/// This doesn't include the [`crate::ListingFileServer::route_base`] or
+	/// the [`crate::ListingFileServer::url_prefix`], so unlike
+	/// [`Listing::url`] it reflects how the application routes to the server
+	/// rather than how clients see it.
+	pub mount_base: String,
+	/// The parameters of the request's query that links re-sorting or paging
+	/// through the listing should keep, joined with `&`.
+	///
+	/// This holds the `q`, `recent`, and `format` parameters, still
+	/// percent-encoded, or is empty if none were given.
+	pub link_query: String,
+	/// The `(label, href)` pairs for each directory from the server root down
+	/// to the one being listed, for building breadcrumb navigation.
+	///
+	/// The first crumb is always the root, labelled `/`. The hrefs are
+	/// percent-encoded absolute paths, built the same way as [`Listing::url`].
+	pub breadcrumbs: Vec<(String, String)>,
+	/// The names of the directories from the server root down to the one being
+	/// listed, without any encoding.
+	///
+	/// This is empty for the root directory. Unlike [`Listing::breadcrumbs`],
+	/// no links are built, so renderers can build them however they need.
+	pub segments: Vec<String>,
+	/// The entries found within the directory, in sorted order.
+	pub entries: Vec<ListingEntry>,
+	/// The field the entries are sorted by.
+	pub sort_by: SortBy,
+	/// The order the entries are sorted in.
+	pub sort_order: SortOrder,
+	/// The page of entries shown, if the listing is paginated.
+	///
+	/// When this is set, [`Listing::entries`] only holds the entries on the
+	/// current page.
+	pub pagination: Option<Pagination>,
+	/// The entries of [`Listing::entries`] grouped by file extension, if
+	/// enabled with [`crate::ListingFileServer::group_by_extension`].
+	///
+	/// Each group is an `(extension, entries)` pair, with the extension in
+	/// lowercase and without its leading `.`. Directories come first in a
+	/// group of their own with the extension `/`, followed by the files in
+	/// groups ordered by extension, with files that have no extension in the
+	/// group with the extension `""`. The entries keep their sorted order
+	/// within each group.
+	pub groups: Option<Vec<(String, Vec<ListingEntry>)>>,
+	/// The title configured for the directory, if any.
+	///
+	/// This is only read if enabled with
+	/// [`crate::ListingFileServer::directory_config`].
+	pub title: Option<String>,
+	/// The search query the entries were found with, if this is a search
+	/// rather than a plain listing.
+	///
+	/// Searches are only possible if enabled with
+	/// [`crate::ListingFileServer::enable_search`].
+	pub search: Option<String>,
+	/// The number of most recently modified files requested, if this lists
+	/// the newest files of the subtree rather than the directory's own
+	/// entries.
+	///
+	/// Such listings are only possible if enabled with
+	/// [`crate::ListingFileServer::enable_recent`].
+	pub recent: Option<usize>,
+	/// The contents of the directory's README file, if one was found.
+	///
+	/// README files are only looked for if configured with
+	/// [`crate::ListingFileServer::readme_files`].
+	pub readme: Option<String>,
+	/// Whether entries were left out because the directory has more than the
+	/// [`crate::ListingFileServer::max_listing_entries`].
+	pub truncated: bool,
+	/// Whether the directory has no entries to show, or the search found
+	/// nothing.
+	///
+	/// This accounts for every page of a paginated listing, and ignores the
+	/// parent directory entry added by
+	/// [`crate::ListingFileServer::show_parent_link`], so templates can check
+	/// it instead of counting [`Listing::entries`]. Empty directories are still
+	/// listed as normal.
+	pub empty: bool,
+	/// The number and total size of the entries across every page.
+	pub summary: ListingSummary,
+}
+
+/// Totals for the entries of a listing.
+///
+/// The parent directory entry added by
+/// [`crate::ListingFileServer::show_parent_link`] isn't counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ListingSummary {
+	/// The number of files, including those of unknown size.
+	pub files: usize,
+	/// The number of directories.
+	pub directories: usize,
+	/// The total size of the files in bytes, leaving out those of unknown
+	/// size.
+	pub total_size: u64,
+}
+
+impl ListingSummary {
+	/// Totals `entries`.
+	pub(crate) fn of(entries: &[ListingEntry]) -> Self {
+		entries.iter().fold(Self::default(), |mut summary, entry| {
+			if entry.is_dir {
+				summary.directories += 1;
+			} else {
+				summary.files += 1;
+				summary.total_size += entry.size.unwrap_or(0);
+			}
+			summary
+		})
+	}
+}
+
+/// Groups `entries` by file extension, as described for [`Listing::groups`].
+pub(crate) fn group_by_extension(entries: &[ListingEntry]) -> Vec<(String, Vec<ListingEntry>)> {
+	let mut directories = Vec::new();
+	let mut files: BTreeMap<String, Vec<ListingEntry>> = BTreeMap::new();
+	for entry in entries {
+		if entry.is_dir {
+			directories.push(entry.clone());
+			continue;
+		}
+		let extension = Path::new(&entry.name)
+			.extension()
+			.map(|extension| extension.to_string_lossy().to_lowercase())
+			.unwrap_or_default();
+		files.entry(extension).or_default().push(entry.clone());
+	}
+	let mut groups = Vec::with_capacity(files.len() + 1);
+	if !directories.is_empty() {
+		groups.push((String::from('/'), directories));
+	}
+	groups.extend(files);
+	groups
+}
+
+/// The position of a paginated listing's page within the whole listing.
+///
+/// Pages are numbered from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Pagination {
+	/// The number of the current page.
+	pub page: usize,
+	/// The maximum number of entries on each page.
+	pub per_page: usize,
+	/// The total number of pages, which is always at least 1.
+	pub page_count: usize,
+	/// The total number of entries across all pages.
+	pub total_entries: usize,
+}
+
+/// Keeps only the entries on page `page` of `entries`, with `per_page` entries
+/// to a page.
+///
+/// A page past the end of the listing is left empty.
+pub(crate) fn paginate<T>(entries: &mut Vec<T>, page: usize, per_page: usize) -> Pagination {
+	let total_entries = entries.len();
+	let start = page
+		.saturating_sub(1)
+		.saturating_mul(per_page)
+		.min(total_entries);
+	let end = start.saturating_add(per_page).min(total_entries);
+	entries.truncate(end);
+	entries.drain(..start);
+	Pagination {
+		page,
+		per_page,
+		page_count: total_entries.div_ceil(per_page).max(1),
+		total_entries,
+	}
+}
+
+/// Builds the breadcrumbs for `directory`, which must begin and end with a
+/// `/`, served from beneath the mount point `base`.
+pub(crate) fn breadcrumbs(base: &str, directory: &str) -> Vec<(String, String)> {
+	let mut href = base.trim_end_matches('/').to_owned();
+	href.push('/');
+	let mut crumbs = vec![(String::from('/'), href.clone())];
+	for segment in directory_segments(directory) {
+		href.push_str(&encode_path_segment(&segment));
+		href.push('/');
+		crumbs.push((segment, href.clone()));
+	}
+	crumbs
+}
+
+/// Splits `directory` into the names of the directories from the server root
+/// down to it.
+pub(crate) fn directory_segments(directory: &str) -> Vec<String> {
+	directory
+		.split('/')
+		.filter(|segment| !segment.is_empty())
+		.map(ToOwned::to_owned)
+		.collect()
+}
@@ -0,0 +1,55 @@
+// Uses
+use std::{fmt::Write, path::Path};
+
+use rocket::http::ContentType;
+
+use crate::Listing;
+
+/// The extensions of the files included in playlists by default, of common
+/// audio and video formats.
+pub(crate) const DEFAULT_PLAYLIST_EXTENSIONS: &[&str] = &[
+	"aac", "avi", "flac", "m4a", "m4v", "mka", "mkv", "mov", "mp3", "mp4", "oga", "ogg", "ogv",
+	"opus", "wav", "webm", "wma", "wmv",
+];
+
+/// The content type of M3U playlists.
+pub(crate) fn playlist_content_type() -> ContentType {
+	ContentType::new("audio", "x-mpegurl")
+}
+
+/// Gets the filename a playlist of `listing` is downloaded as, named after
+/// the directory.
+pub(crate) fn playlist_filename(listing: &Listing) -> String {
+	let name = listing
+		.directory
+		.trim_end_matches('/')
+		.rsplit('/')
+		.next()
+		.filter(|name| !name.is_empty())
+		.unwrap_or("playlist");
+	format!("{name}.m3u")
+}
+
+/// Renders the files of `listing` with any of `extensions` as an extended M3U
+/// playlist, in the listing's order.
+///
+/// Links are made absolute by prefixing them with `origin`, such as
+/// `https://example.com`, which may be empty if it isn't known.
+pub(crate) fn render_m3u(listing: &Listing, origin: &str, extensions: &[String]) -> String {
+	let directory_href = &listing.url;
+
+	let mut playlist = String::from("#EXTM3U\n");
+	for file in listing.entries.iter().filter(|entry| !entry.is_dir) {
+		let is_media = Path::new(&file.name).extension().is_some_and(|extension| {
+			extensions.contains(&extension.to_string_lossy().to_lowercase())
+		});
+		if !is_media {
+			continue;
+		}
+		// Line breaks would end the entry early
+		let title = file.display_name.replace(['\r', '\n'], " ");
+		let _ = writeln!(playlist, "#EXTINF:-1,{title}");
+		let _ = writeln!(playlist, "{origin}{directory_href}{}", file.href);
+	}
+	playlist
+}
@@ -0,0 +1,132 @@
+// Uses
+use std::{
+	io::{Cursor, SeekFrom},
+	time::SystemTime,
+};
+
+use httpdate::{fmt_http_date, parse_http_date};
+use rocket::{
+	http::{Header, Status},
+	tokio::{
+		fs::File,
+		io::{AsyncReadExt, AsyncSeekExt},
+	},
+	Request,
+	Response,
+};
+
+use crate::caching::whole_seconds;
+
+/// The byte range requested by a `Range` header, if it should be honoured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum RequestedRange {
+	/// The whole file should be served, because no single range was requested
+	/// or `If-Range` didn't match.
+	Whole,
+	/// The inclusive byte range should be served.
+	Partial(u64, u64),
+	/// The requested range lies outside of the file.
+	Unsatisfiable,
+}
+
+impl RequestedRange {
+	/// Determines the range of a file of `len` bytes, last modified at
+	/// `modified`, requested by `req`.
+	///
+	/// Only single ranges are honoured. Requests for several ranges are
+	/// served the whole file, which is always allowed and is usually cheaper
+	/// than a multipart response. An `If-Range` precondition is only
+	/// satisfied by a date matching `modified`, since files have no strong
+	/// entity tag.
+	pub(crate) fn from_request(req: &Request<'_>, len: u64, modified: Option<SystemTime>) -> Self {
+		let Some(range) = req.headers().get_one("Range") else {
+			return RequestedRange::Whole;
+		};
+		if let Some(if_range) = req.headers().get_one("If-Range") {
+			let matches = parse_http_date(if_range)
+				.ok()
+				.zip(modified)
+				.is_some_and(|(date, modified)| whole_seconds(date) == whole_seconds(modified));
+			if !matches {
+				return RequestedRange::Whole;
+			}
+		}
+		parse_range(range, len)
+	}
+
+	/// Applies the range to `response`, which serves `file` of `len` bytes.
+	///
+	/// Responses for the whole file are left as-is, apart from advertising
+	/// range support.
+	pub(crate) async fn apply(self, response: &mut Response<'_>, file: Option<File>, len: u64) {
+		response.set_header(Header::new("Accept-Ranges", "bytes"));
+		match self {
+			RequestedRange::Whole => {}
+			RequestedRange::Partial(start, end) => {
+				let Some(mut file) = file else {
+					return;
+				};
+				if file.seek(SeekFrom::Start(start)).await.is_err() {
+					return;
+				}
+				let part_len = end - start + 1;
+				response.set_status(Status::PartialContent);
+				response.set_header(Header::new(
+					"Content-Range",
+					format!("bytes {start}-{end}/{len}"),
+				));
+				response.set_header(Header::new("Content-Length", part_len.to_string()));
+				response.set_streamed_body(file.take(part_len));
+			}
+			RequestedRange::Unsatisfiable => {
+				response.set_status(Status::RangeNotSatisfiable);
+				response.set_header(Header::new("Content-Range", format!("bytes */{len}")));
+				response.set_sized_body(0, Cursor::new(Vec::new()));
+			}
+		}
+	}
+}
+
+/// Parses the `Range` header value `range` for a file of `len` bytes.
+///
+/// Anything other than a single, well-formed byte range is ignored, as the
+/// specification allows.
+fn parse_range(range: &str, len: u64) -> RequestedRange {
+	let Some(spec) = range
+		.split_once('=')
+		.filter(|(unit, _)| unit.trim().eq_ignore_ascii_case("bytes"))
+		.map(|(_, spec)| spec.trim())
+	else {
+		return RequestedRange::Whole;
+	};
+	if spec.contains(',') {
+		return RequestedRange::Whole;
+	}
+	let Some((start, end)) = spec.split_once('-') else {
+		return RequestedRange::Whole;
+	};
+	let (start, end) = (start.trim(), end.trim());
+	let range = match (start.parse::<u64>(), end.parse::<u64>()) {
+		// `bytes=start-end`
+		(Ok(start), Ok(end)) if start <= end => (start, end.min(len.saturating_sub(1))),
+		// `bytes=start-`
+		(Ok(start), Err(_)) if end.is_empty() => (start, len.saturating_sub(1)),
+		// `bytes=-suffix`
+		(Err(_), Ok(suffix)) if start.is_empty() => {
+			if suffix == 0 {
+				return RequestedRange::Unsatisfiable;
+			}
+			(len.saturating_sub(suffix), len.saturating_sub(1))
+		}
+		_ => return RequestedRange::Whole,
+	};
+	if range.0 >= len {
+		return RequestedRange::Unsatisfiable;
+	}
+	RequestedRange::Partial(range.0, range.1)
+}
+
+/// Formats `modified` as the `Last-Modified` header of a file.
+pub(crate) fn last_modified_header(modified: SystemTime) -> Header<'static> {
+	Header::new("Last-Modified", fmt_http_date(modified))
+}
@@ -0,0 +1,546 @@
+// Uses
+use std::{
+	collections::{hash_map, HashMap},
+	ffi::OsString,
+	fs::{self, DirEntry, Metadata},
+	io,
+	path::{Path, PathBuf},
+};
+
+use glob::Pattern;
+
+use crate::{
+	encoding::encode_path_segment,
+	entry::{guess_type, SizeUnits},
+	util::{is_included, is_within_root},
+	EntryFilter,
+	ListingEntry,
+};
+
+/// The rules for which entries of a directory are shown and how they're read,
+/// shared by listings, archives, and directory sizes so that they all agree.
+///
+/// Everything here reads the filesystem synchronously, so it's meant to be run
+/// on a blocking thread.
+#[derive(Debug, Clone)]
+pub(crate) struct EntryReader {
+	/// The roots of the server, used to check symlinks.
+	pub roots: Vec<PathBuf>,
+	/// Whether to leave out dotfiles.
+	pub hide_dotfiles: bool,
+	/// Whether to leave out entries whose names aren't valid UTF-8, rather
+	/// than converting them lossily.
+	pub skip_non_utf8_names: bool,
+	/// The patterns of entry names to leave out.
+	pub exclude_patterns: Vec<Pattern>,
+	/// The patterns of the only entry names to show, if any.
+	pub include_patterns: Vec<Pattern>,
+	/// Whether to show directories regardless of `include_patterns`.
+	pub always_include_dirs: bool,
+	/// The patterns of entry names to mark as hidden, besides dotfiles.
+	pub hidden_patterns: Vec<Pattern>,
+	/// The names of the files that configure the server, which are never
+	/// shown.
+	pub reserved_names: Vec<String>,
+	/// Which entries are shown, by whether they're directories.
+	pub entry_filter: EntryFilter,
+	/// Whether to follow symlinks that resolve outside of `roots`.
+	pub follow_symlinks: bool,
+	/// Whether to follow symlinks to files.
+	pub follow_file_symlinks: bool,
+	/// Whether to follow symlinks to directories.
+	pub follow_dir_symlinks: bool,
+	/// Whether to read the targets of symlinks.
+	pub show_symlink_targets: bool,
+	/// The units that entry sizes are formatted in.
+	pub size_units: SizeUnits,
+}
+
+/// An entry read from one or more of a set of merged directories.
+#[derive(Debug, Clone)]
+pub(crate) struct MergedEntry {
+	/// The entry, as listed.
+	pub entry: ListingEntry,
+	/// The path of the entry, or for a directory, the paths of every directory
+	/// of the same name, in order.
+	pub paths: Vec<PathBuf>,
+	/// Whether the entry's contents can be read through it, which isn't the
+	/// case for symlinks that aren't followed.
+	pub is_followed: bool,
+}
+
+/// An entry found while walking a subtree.
+#[derive(Debug, Clone)]
+pub(crate) struct TreeEntry {
+	/// The path of the entry relative to the top of the subtree, with `/`
+	/// separating its names.
+	pub relative: String,
+	/// The entry itself.
+	pub merged: MergedEntry,
+}
+
+impl EntryReader {
+	/// Creates a reader that shows every entry of `roots` and follows every
+	/// symlink.
+	pub(crate) fn new(roots: Vec<PathBuf>) -> Self {
+		EntryReader {
+			roots,
+			hide_dotfiles: false,
+			skip_non_utf8_names: false,
+			exclude_patterns: Vec::new(),
+			include_patterns: Vec::new(),
+			always_include_dirs: true,
+			hidden_patterns: Vec::new(),
+			reserved_names: Vec::new(),
+			entry_filter: EntryFilter::All,
+			follow_symlinks: true,
+			follow_file_symlinks: true,
+			follow_dir_symlinks: true,
+			show_symlink_targets: false,
+			size_units: SizeUnits::default(),
+		}
+	}
+
+	/// Gets the name of the directory entry named `file_name`, unless its name
+	/// alone leaves it out.
+	pub(crate) fn listed_name(&self, file_name: OsString) -> Option<String> {
+		let name = match file_name.into_string() {
+			Ok(name) => name,
+			Err(_) if self.skip_non_utf8_names => return None,
+			Err(name) => name.to_string_lossy().into_owned(),
+		};
+		if (self.hide_dotfiles && name.starts_with('.'))
+			|| self.reserved_names.contains(&name)
+			|| self
+				.exclude_patterns
+				.iter()
+				.any(|pattern| pattern.matches(&name))
+		{
+			return None;
+		}
+		Some(name)
+	}
+
+	/// Resolves `path`, relative to the roots, against each root in order.
+	///
+	/// Paths that resolve outside of their root are left out, as described
+	/// for [`EntryReader::resolves_within`], as are paths through symlinks of
+	/// a kind that isn't followed.
+	pub(crate) fn candidates(&self, path: &Path) -> Vec<PathBuf> {
+		self.roots
+			.iter()
+			.filter(|root| self.follows_symlinks_within(root, path))
+			.map(|root| (root, root.join(path)))
+			.filter(|(root, path)| self.resolves_within(root, path))
+			.map(|(_, path)| path)
+			.collect()
+	}
+
+	/// Checks whether `path`, beneath `root`, canonicalizes to a location
+	/// within the canonicalized `root`.
+	///
+	/// If symlinks are followed, a path that escapes the root is still allowed
+	/// as long as it does so through a symlink beneath the root, and a path
+	/// that doesn't exist is allowed so that it's handled as missing. Paths
+	/// that can't be checked are treated as outside of the root.
+	pub(crate) fn resolves_within(&self, root: &Path, path: &Path) -> bool {
+		if is_within_root(path, root) {
+			return true;
+		}
+		if !self.follow_symlinks {
+			return false;
+		}
+		let Ok(relative) = path.strip_prefix(root) else {
+			return false;
+		};
+		if matches!(path.try_exists(), Ok(false)) {
+			return true;
+		}
+		let mut current = root.to_path_buf();
+		relative.components().any(|component| {
+			current.push(component);
+			fs::symlink_metadata(&current).is_ok_and(|m| m.is_symlink())
+		})
+	}
+
+	/// Checks whether `path`, beneath one of the roots, resolves within it, as
+	/// [`EntryReader::resolves_within`] does.
+	pub(crate) fn resolves_within_roots(&self, path: &Path) -> bool {
+		self.roots
+			.iter()
+			.filter(|root| path.starts_with(root))
+			.any(|root| self.resolves_within(root, path))
+	}
+
+	/// Checks whether every symlink along `path`, relative to `base`, is of a
+	/// kind that's followed.
+	///
+	/// Components that don't exist are ignored, so that missing paths are
+	/// still handled as such.
+	pub(crate) fn follows_symlinks_within(&self, base: &Path, path: &Path) -> bool {
+		if self.follow_file_symlinks && self.follow_dir_symlinks {
+			return true;
+		}
+		let mut current = base.to_path_buf();
+		for component in path.components() {
+			current.push(component);
+			if !fs::symlink_metadata(&current).is_ok_and(|m| m.is_symlink()) {
+				continue;
+			}
+			if fs::metadata(&current).is_ok_and(|target| {
+				!follows_symlink_to(&target, self.follow_file_symlinks, self.follow_dir_symlinks)
+			}) {
+				return false;
+			}
+		}
+		true
+	}
+
+	/// Checks whether `entry` is shown by the entry filter and the include
+	/// patterns.
+	pub(crate) fn shows(&self, entry: &ListingEntry) -> bool {
+		self.entry_filter.shows(entry)
+			&& is_included(
+				&entry.name,
+				entry.is_dir,
+				&self.include_patterns,
+				self.always_include_dirs,
+			)
+	}
+
+	/// Checks whether the entry name `name` is a dotfile or matches any of the
+	/// hidden patterns.
+	fn is_hidden(&self, name: &str) -> bool {
+		name.starts_with('.')
+			|| self
+				.hidden_patterns
+				.iter()
+				.any(|pattern| pattern.matches(name))
+	}
+
+	/// Reads the directory entry `dir_entry`, unless its name leaves it out.
+	///
+	/// The entry's type usually comes for free with the directory read, so the
+	/// entry is only looked up again by path if it's a symlink or its type is
+	/// unknown. Symlinks are followed unless they resolve outside of every
+	/// root and `follow_symlinks` is disabled, or their target is of a kind
+	/// that isn't followed.
+	pub(crate) fn read_entry(&self, dir_entry: &DirEntry) -> Option<MergedEntry> {
+		let name = self.listed_name(dir_entry.file_name())?;
+		let path = dir_entry.path();
+		let (details, is_followed) = match dir_entry.file_type() {
+			Ok(file_type) if !file_type.is_symlink() => (
+				EntryDetails {
+					metadata: dir_entry.metadata().ok(),
+					is_symlink: false,
+					symlink_target: None,
+				},
+				true,
+			),
+			Ok(_) => {
+				let target = if self.follow_symlinks
+					|| self.roots.iter().any(|root| is_within_root(&path, root))
+				{
+					fs::metadata(&path).ok().filter(|target| {
+						follows_symlink_to(
+							target,
+							self.follow_file_symlinks,
+							self.follow_dir_symlinks,
+						)
+					})
+				} else {
+					None
+				};
+				let is_followed = target.is_some();
+				(
+					EntryDetails {
+						metadata: target.or_else(|| dir_entry.metadata().ok()),
+						is_symlink: true,
+						symlink_target: if self.show_symlink_targets {
+							fs::read_link(&path)
+								.ok()
+								.map(|target| target.to_string_lossy().into_owned())
+						} else {
+							None
+						},
+					},
+					is_followed,
+				)
+			}
+			Err(_) => (
+				EntryDetails {
+					metadata: fs::metadata(&path).ok(),
+					is_symlink: false,
+					symlink_target: None,
+				},
+				true,
+			),
+		};
+		let is_hidden = self.is_hidden(&name);
+		Some(MergedEntry {
+			entry: listing_entry(name, &path, details, is_hidden, self.size_units),
+			paths: vec![path],
+			is_followed,
+		})
+	}
+
+	/// Reads the entries of `directories`, merging them by name.
+	///
+	/// Where several directories have an entry with the same name, the first
+	/// one wins, except that the entry is treated as a directory if it's a
+	/// directory in any of them. Entries are left out by their names only, so
+	/// [`EntryReader::shows`] still needs to be checked.
+	///
+	/// # Errors
+	///
+	/// Returns the error encountered while opening any of the directories.
+	/// Entries that can't be read are skipped.
+	pub(crate) fn merged_entries(&self, directories: &[PathBuf]) -> io::Result<Vec<MergedEntry>> {
+		let mut merged: Vec<MergedEntry> = Vec::new();
+		let mut indices: HashMap<String, usize> = HashMap::new();
+		for directory in directories {
+			for dir_entry in fs::read_dir(directory)?.filter_map(Result::ok) {
+				let Some(read) = self.read_entry(&dir_entry) else {
+					continue;
+				};
+				match indices.entry(read.entry.name.clone()) {
+					hash_map::Entry::Occupied(index) => {
+						let existing = &mut merged[*index.get()];
+						if !read.entry.is_dir {
+							continue;
+						}
+						if existing.entry.is_dir {
+							existing.paths.extend(read.paths);
+						} else {
+							*existing = read;
+						}
+					}
+					hash_map::Entry::Vacant(index) => {
+						index.insert(merged.len());
+						merged.push(read);
+					}
+				}
+			}
+		}
+		Ok(merged)
+	}
+
+	/// Reads the entries of `directories` as [`EntryReader::merged_entries`]
+	/// does, without their paths.
+	///
+	/// # Errors
+	///
+	/// Returns the error encountered while opening any of the directories.
+	pub(crate) fn read_entries(&self, directories: &[PathBuf]) -> io::Result<Vec<ListingEntry>> {
+		Ok(self
+			.merged_entries(directories)?
+			.into_iter()
+			.map(|merged| merged.entry)
+			.collect())
+	}
+
+	/// Walks the merged subtree of `directories`, collecting every entry that's
+	/// shown and followed, with each directory before its contents.
+	///
+	/// At most `max_depth` directory levels are descended. Directories that
+	/// are already being walked further up aren't walked again, so symlink
+	/// cycles end, and directories that can't be read are skipped.
+	///
+	/// # Errors
+	///
+	/// Returns the error encountered while opening any of `directories`
+	/// themselves.
+	pub(crate) fn walk_tree(
+		&self,
+		directories: &[PathBuf],
+		max_depth: usize,
+	) -> io::Result<Vec<TreeEntry>> {
+		let mut tree = Vec::new();
+		let mut ancestors = directories
+			.iter()
+			.filter_map(|directory| directory.canonicalize().ok())
+			.collect::<Vec<_>>();
+		let entries = self.merged_entries(directories)?;
+		self.walk_entries(entries, "", max_depth, &mut ancestors, &mut tree);
+		Ok(tree)
+	}
+
+	/// Adds `entries`, and recursively the contents of their directories, to
+	/// `tree`, with their relative paths prefixed by `prefix`.
+	///
+	/// `ancestors` holds the canonicalized paths of the directories currently
+	/// being walked.
+	fn walk_entries(
+		&self,
+		entries: Vec<MergedEntry>,
+		prefix: &str,
+		depth_left: usize,
+		ancestors: &mut Vec<PathBuf>,
+		tree: &mut Vec<TreeEntry>,
+	) {
+		for mut merged in entries {
+			if !merged.is_followed || !self.shows(&merged.entry) {
+				continue;
+			}
+			let relative = format!("{prefix}{}", merged.entry.name);
+			if !merged.entry.is_dir {
+				tree.push(TreeEntry { relative, merged });
+				continue;
+			}
+			merged.paths.retain(|path| {
+				path.canonicalize()
+					.is_ok_and(|canonical| !ancestors.contains(&canonical))
+			});
+			let Some(canonical) = merged
+				.paths
+				.first()
+				.and_then(|path| path.canonicalize().ok())
+			else {
+				continue;
+			};
+			let children = if depth_left > 0 {
+				self.merged_entries(&merged.paths).ok()
+			} else {
+				None
+			};
+			let child_prefix = format!("{relative}/");
+			tree.push(TreeEntry { relative, merged });
+			if let Some(children) = children {
+				ancestors.push(canonical);
+				self.walk_entries(children, &child_prefix, depth_left - 1, ancestors, tree);
+				ancestors.pop();
+			}
+		}
+	}
+}
+
+/// Keeps the entries of `tree` that `keep` accepts, leaving out the contents
+/// of every directory it rejects along with the directory itself.
+pub(crate) fn prune_tree<F>(tree: Vec<TreeEntry>, mut keep: F) -> Vec<TreeEntry>
+where
+	F: FnMut(&TreeEntry) -> bool,
+{
+	let mut rejected: Vec<String> = Vec::new();
+	tree.into_iter()
+		.filter(|tree_entry| {
+			if rejected
+				.iter()
+				.any(|prefix| tree_entry.relative.starts_with(prefix.as_str()))
+			{
+				return false;
+			}
+			if keep(tree_entry) {
+				return true;
+			}
+			if tree_entry.merged.entry.is_dir {
+				rejected.push(format!("{}/", tree_entry.relative));
+			}
+			false
+		})
+		.collect()
+}
+
+/// Checks whether symlinks to targets with the metadata `target` are followed,
+/// by the kind of the target.
+pub(crate) fn follows_symlink_to(
+	target: &Metadata,
+	follow_file_symlinks: bool,
+	follow_dir_symlinks: bool,
+) -> bool {
+	if target.is_dir() {
+		follow_dir_symlinks
+	} else {
+		follow_file_symlinks
+	}
+}
+
+/// The details of a directory entry needed to build its listing entry.
+struct EntryDetails {
+	/// The metadata of the entry, if it could be read.
+	///
+	/// For a followed symlink, this is the metadata of its target.
+	metadata: Option<Metadata>,
+	/// Whether the entry is a symlink.
+	is_symlink: bool,
+	/// The target of the entry, if it's a symlink and targets are shown.
+	symlink_target: Option<String>,
+}
+
+/// Builds the listing entry for the directory entry `name` at `path`, from
+/// `details`, with its size formatted in `size_units`.
+fn listing_entry(
+	name: String,
+	path: &Path,
+	details: EntryDetails,
+	is_hidden: bool,
+	size_units: SizeUnits,
+) -> ListingEntry {
+	let entry_metadata = details.metadata.as_ref();
+	let is_dir = entry_metadata.is_some_and(Metadata::is_dir);
+	let (content_type, kind) = guess_type(&name, is_dir);
+	let mut href = encode_path_segment(&name);
+	if is_dir {
+		href.push('/');
+	}
+	let size = entry_metadata.filter(|m| m.is_file()).map(Metadata::len);
+	ListingEntry {
+		display_name: name.clone(),
+		name,
+		href,
+		is_dir,
+		size,
+		human_size: size.map(|size| size_units.format(size)),
+		modified: entry_metadata.and_then(|m| m.modified().ok()),
+		content_type,
+		kind,
+		is_symlink: details.is_symlink,
+		symlink_target: details.symlink_target,
+		is_hidden,
+		mode: entry_metadata.and_then(mode),
+		is_readable: entry_metadata.is_some_and(|m| is_readable(path, m)),
+	}
+}
+
+/// Gets the Unix permission bits from `metadata`.
+#[cfg(unix)]
+fn mode(metadata: &Metadata) -> Option<u32> {
+	use std::os::unix::fs::PermissionsExt;
+
+	Some(metadata.permissions().mode() & 0o7777)
+}
+
+/// Gets the Unix permission bits from `metadata`, which aren't available on
+/// this platform.
+#[cfg(not(unix))]
+fn mode(_: &Metadata) -> Option<u32> {
+	None
+}
+
+/// Checks whether the server is able to read the entry at `path`, with the
+/// metadata `metadata`.
+///
+/// Directories must also be searchable. The check is left to the OS, using
+/// the effective user and groups of the process, so that privileged users and
+/// access control lists are accounted for.
+#[cfg(unix)]
+fn is_readable(path: &Path, metadata: &Metadata) -> bool {
+	use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+	let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+		return false;
+	};
+	let mode = if metadata.is_dir() {
+		libc::R_OK | libc::X_OK
+	} else {
+		libc::R_OK
+	};
+	// SAFETY: `path` is a valid C string that outlives the call
+	unsafe { libc::faccessat(libc::AT_FDCWD, path.as_ptr(), mode, libc::AT_EACCESS) == 0 }
+}
+
+/// Checks whether the server is able to read the entry at `path`, which is
+/// always assumed on this platform.
+#[cfg(not(unix))]
+fn is_readable(_: &Path, _: &Metadata) -> bool {
+	true
+}
@@ -0,0 +1,143 @@
+// Uses
+use std::{
+	collections::{hash_map::DefaultHasher, VecDeque},
+	hash::{Hash, Hasher},
+	sync::{Mutex, PoisonError},
+	time::SystemTime,
+};
+
+use crate::{Listing, ListingSummary, SortBy, SortOrder};
+
+/// A bounded, least-recently-used cache of the HTML of rendered listings.
+///
+/// Renders are keyed by what the page depends on besides the directory, and
+/// are only reused while the directory and its entries appear unchanged.
+#[derive(Debug)]
+pub(crate) struct RenderCache {
+	/// The most renders kept.
+	capacity: usize,
+	/// The cached renders, most recently used first.
+	renders: Mutex<VecDeque<CachedRender>>,
+}
+
+/// A cached render of a listing.
+#[derive(Debug)]
+struct CachedRender {
+	key: RenderKey,
+	stamp: RenderStamp,
+	html: String,
+}
+
+/// What a rendered listing depends on, besides the state of the directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RenderKey {
+	/// The URL path of the listing, as seen by clients.
+	url: String,
+	/// The URL path of the request, which decides whether links are relative
+	/// to a `<base>`.
+	request_path: String,
+	/// The query parameters the listing's links keep.
+	link_query: String,
+	sort_by: SortBy,
+	sort_order: SortOrder,
+	/// The page number and the number of entries on each page, if paginated.
+	page: Option<(usize, usize)>,
+}
+
+/// The state of a directory that a listing of it was rendered from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RenderStamp {
+	/// The latest modified time of the directory or any of the entries shown.
+	modified: Option<SystemTime>,
+	/// A hash of the names, labels, sizes, and modified times of the entries
+	/// shown.
+	entries: u64,
+	/// The title configured for the directory.
+	title: Option<String>,
+	/// The contents of the directory's README file.
+	readme: Option<String>,
+	/// The totals for the entries across every page.
+	summary: ListingSummary,
+}
+
+impl RenderCache {
+	/// Creates an empty cache that keeps up to `capacity` renders.
+	pub(crate) fn new(capacity: usize) -> Self {
+		RenderCache {
+			capacity,
+			renders: Mutex::new(VecDeque::with_capacity(capacity)),
+		}
+	}
+
+	/// The most renders kept.
+	pub(crate) fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	/// Gets the cached HTML of `listing`, of a directory last modified at
+	/// `dir_modified`, or renders it with `render` and caches it.
+	///
+	/// A cached render is discarded once the directory's modified time, its
+	/// title or README, or any of the entries shown change.
+	pub(crate) fn get_or_render<F>(
+		&self,
+		listing: &Listing,
+		dir_modified: Option<SystemTime>,
+		render: F,
+	) -> String
+	where
+		F: FnOnce(&Listing) -> String,
+	{
+		let key = RenderKey {
+			url: listing.url.clone(),
+			request_path: listing.request_path.clone(),
+			link_query: listing.link_query.clone(),
+			sort_by: listing.sort_by,
+			sort_order: listing.sort_order,
+			page: listing
+				.pagination
+				.as_ref()
+				.map(|pagination| (pagination.page, pagination.per_page)),
+		};
+		let mut hasher = DefaultHasher::new();
+		for entry in &listing.entries {
+			(&entry.name, &entry.display_name, entry.size, entry.modified).hash(&mut hasher);
+		}
+		let stamp = RenderStamp {
+			modified: listing
+				.entries
+				.iter()
+				.filter_map(|entry| entry.modified)
+				.chain(dir_modified)
+				.max(),
+			entries: hasher.finish(),
+			title: listing.title.clone(),
+			readme: listing.readme.clone(),
+			summary: listing.summary,
+		};
+
+		{
+			let mut renders = self.renders.lock().unwrap_or_else(PoisonError::into_inner);
+			if let Some(index) = renders.iter().position(|cached| cached.key == key) {
+				let cached = renders.remove(index).expect("the index was just found");
+				if cached.stamp == stamp {
+					let html = cached.html.clone();
+					renders.push_front(cached);
+					return html;
+				}
+			}
+		}
+
+		// Render without holding the lock, so other listings aren't held up
+		let html = render(listing);
+		let mut renders = self.renders.lock().unwrap_or_else(PoisonError::into_inner);
+		renders.retain(|cached| cached.key != key);
+		renders.push_front(CachedRender {
+			key,
+			stamp,
+			html: html.clone(),
+		});
+		renders.truncate(self.capacity);
+		html
+	}
+}
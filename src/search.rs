@@ -0,0 +1,43 @@
+// Uses
+use glob::{MatchOptions, Pattern};
+
+/// A search query for entry names, as given in the `q` query parameter.
+#[derive(Debug, Clone)]
+pub(crate) enum SearchQuery {
+	/// Matches names containing the text, ignoring case.
+	Substring(String),
+	/// Matches names against a glob pattern, ignoring case.
+	Glob(Pattern),
+}
+
+impl SearchQuery {
+	/// Parses `query`, treating it as a glob pattern if it contains any glob
+	/// syntax and as plain text otherwise.
+	///
+	/// Returns [`None`] if the query is empty.
+	pub(crate) fn parse(query: &str) -> Option<Self> {
+		if query.is_empty() {
+			return None;
+		}
+		if query.contains(['*', '?', '[']) {
+			if let Ok(pattern) = Pattern::new(query) {
+				return Some(SearchQuery::Glob(pattern));
+			}
+		}
+		Some(SearchQuery::Substring(query.to_lowercase()))
+	}
+
+	/// Checks whether the entry name `name` matches the query.
+	pub(crate) fn matches(&self, name: &str) -> bool {
+		match self {
+			SearchQuery::Substring(text) => name.to_lowercase().contains(text.as_str()),
+			SearchQuery::Glob(pattern) => pattern.matches_with(
+				name,
+				MatchOptions {
+					case_sensitive: false,
+					..MatchOptions::new()
+				},
+			),
+		}
+	}
+}
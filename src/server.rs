@@ -1,23 +1,76 @@
 // Uses
 use std::{
-	cmp::Reverse,
-	fs::read_dir,
+	cmp::Ordering,
+	collections::{HashMap, HashSet, VecDeque},
+	fmt,
+	fs::{self, Metadata},
+	io::{self, Write},
 	path::{Path, PathBuf},
+	sync::Arc,
+	time::SystemTime,
 };
 
+use glob::Pattern;
 use rocket::{
 	async_trait,
+	debug_,
 	error,
+	fairing::AdHoc,
 	figment,
 	fs::{NamedFile, Options},
-	http::{ext::IntoOwned, uri::Segments, Method},
-	response::Redirect,
+	http::{ext::IntoOwned, uri::Segments, ContentType, Header, Method, RawStr, Status},
+	response::{self, content::Html, Redirect, Responder},
 	route::{Handler, Outcome, Route},
+	serde::json::Json,
+	tokio::{
+		self,
+		fs::{metadata, read_to_string, symlink_metadata},
+		io::duplex,
+		task::spawn_blocking,
+	},
 	warn_,
 	Data,
 	Request,
+	Response,
 };
-use rocket_dyn_templates::Template;
+
+use crate::{
+	access::{AccessEvent, AccessKind},
+	archive::{archive_response, ArchiveFormat},
+	caching::{adjoin_vary, is_file_fresh, CacheControl, ListingValidators},
+	compression::{accepts_brotli, accepts_gzip, compress, is_compressible},
+	cors::CorsConfig,
+	dir_size::dir_size,
+	directory_config::{DirectoryConfig, DIRECTORY_CONFIG_FILE},
+	encoding::attachment_disposition,
+	entry::SizeUnits,
+	feed::{render_atom, FeedFormat},
+	format::{negotiate, ListingFormat},
+	html::{render_head, render_listing, render_row, render_tail},
+	listing::{breadcrumbs, directory_segments, group_by_extension, paginate, ListingSummary},
+	playlist::{playlist_content_type, playlist_filename, render_m3u, DEFAULT_PLAYLIST_EXTENSIONS},
+	range::{last_modified_header, RequestedRange},
+	reader::{prune_tree, EntryReader, TreeEntry},
+	render_cache::RenderCache,
+	search::SearchQuery,
+	sort::{sort_entries, SortBy, SortOptions, SortOrder},
+	text::render_text,
+	trailing_slash::TrailingSlash,
+	util::{is_included, is_plain_relative, BlockingWriter},
+	EntryFilter,
+	EntryKind,
+	Listing,
+	ListingEntry,
+};
+#[cfg(feature = "image")]
+use crate::{
+	entry::guess_type,
+	thumbnail::{thumbnail, MAX_THUMBNAIL_SIZE},
+};
+
+/// The query parameters that links re-sorting or paging through a listing
+/// keep, since they select what's listed or how.
+const LINK_QUERY_PARAMETERS: &[&str] = &["q", "recent", "format"];
 
 /// A feature-equivalent copy of [`rocket::fs::FileServer`] that provides
 /// directory listings when a directory is requested.
@@ -30,18 +83,159 @@ use rocket_dyn_templates::Template;
 /// [`rocket::fs::Options::Index`] option has additional considerations.
 /// If enabled, the index file will be served first if available, and directory
 /// listing will only occur if there is no index file.
-#[derive(Debug, Clone)]
-pub struct ListingFileServer<R: 'static + Fn(String, Vec<String>) -> Template + Send + Sync + Clone>
-{
-	root: PathBuf,
+///
+/// Only `GET` and `HEAD` requests are routed, along with `OPTIONS` if
+/// [`ListingFileServer::cors`] is configured. WebDAV methods such as `PROPFIND`
+/// aren't supported, since Rocket's [`Method`] only covers the standard HTTP
+/// methods and rejects the rest, so the server can't be mounted as a WebDAV
+/// source. [`ListingFileServer::enable_json`] offers the same details of each
+/// entry to clients that can be adapted instead.
+#[derive(Clone)]
+pub struct ListingFileServer {
+	roots: Vec<PathBuf>,
+	/// The open directory that the root is resolved through, if created with
+	/// [`ListingFileServer::try_from_dir`].
+	root_handle: Option<Arc<fs::File>>,
 	options: Options,
 	rank: isize,
-	template_renderer: R,
+	route_base: String,
+	/// The custom listing renderer, or [`None`] for the built-in HTML one.
+	renderer: Option<ListingRenderer>,
+	inline_css: Option<String>,
+	sort_by: SortBy,
+	sort_order: SortOrder,
+	sort_options: SortOptions,
+	comparator: Option<Comparator>,
+	group_by_extension: bool,
+	index_files: Vec<String>,
+	index_at_root_only: bool,
+	show_parent_link: bool,
+	follow_symlinks: bool,
+	follow_file_symlinks: bool,
+	follow_dir_symlinks: bool,
+	skip_non_utf8_names: bool,
+	enable_json: bool,
+	enable_text: bool,
+	default_listing_format: ListingFormat,
+	enable_archive_download: bool,
+	cache_listings: bool,
+	render_cache: Option<Arc<RenderCache>>,
+	hide_dotfiles_in_listing: bool,
+	exclude_patterns: Vec<Pattern>,
+	include_patterns: Vec<Pattern>,
+	always_include_dirs: bool,
+	hidden_patterns: Vec<Pattern>,
+	deny_excluded: bool,
+	page_size: Option<usize>,
+	directory_config: bool,
+	access_filter: Option<AccessFilter>,
+	forbid_filtered: bool,
+	enable_search: bool,
+	search_max_depth: usize,
+	search_max_results: usize,
+	enable_recent: bool,
+	recent_max_depth: usize,
+	recent_max_entries: usize,
+	file_cache_control: Option<CacheControl>,
+	listing_cache_control: Option<CacheControl>,
+	enable_feed: bool,
+	enable_playlist: bool,
+	playlist_extensions: Vec<String>,
+	readme_files: Vec<String>,
+	entry_filter: EntryFilter,
+	url_prefix: Option<String>,
+	public_origin: Option<String>,
+	show_symlink_targets: bool,
+	compression: bool,
+	precompressed: bool,
+	content_type_overrides: HashMap<String, ContentType>,
+	force_download_extensions: Vec<String>,
+	max_file_size: Option<u64>,
+	oversized_status: Option<Status>,
+	dotfile_denied_status: Option<Status>,
+	on_access: Option<AccessCallback>,
+	max_listing_entries: Option<usize>,
+	listing_overflow_status: Option<Status>,
+	noindex_marker: Option<String>,
+	max_path_depth: Option<usize>,
+	path_depth_status: Option<Status>,
+	noindex_status: Option<Status>,
+	vanished_status: Option<Status>,
+	dir_suffix: Option<char>,
+	display_name_fn: Option<DisplayNameFn>,
+	size_units: SizeUnits,
+	not_found_renderer: Option<NotFoundRenderer>,
+	error_renderer: Option<ErrorRenderer>,
+	fallback: Option<Arc<dyn Handler>>,
+	trailing_slash: TrailingSlash,
+	normalize_redirect_permanent: bool,
+	stream_listings: bool,
+	stream_entry_renderer: Option<StreamEntryRenderer>,
+	collapse_single_dirs: bool,
+	compute_dir_sizes: bool,
+	dir_size_max_depth: usize,
+	cors: Option<CorsConfig>,
+	#[cfg(feature = "image")]
+	enable_thumbnails: bool,
+	#[cfg(feature = "image")]
+	thumbnail_cache_dir: Option<PathBuf>,
 }
 
-impl<R: 'static + Fn(String, Vec<String>) -> Template + Send + Sync + Clone> ListingFileServer<R> {
+/// The access callback stored by [`ListingFileServer`].
+type AccessCallback = Arc<dyn Fn(&Request<'_>, AccessEvent) + Send + Sync>;
+
+/// The per-request access filter stored by [`ListingFileServer`].
+type AccessFilter = Arc<dyn Fn(&Request<'_>, &Path) -> bool + Send + Sync>;
+
+/// The custom entry comparator stored by [`ListingFileServer`].
+type Comparator = Arc<dyn Fn(&ListingEntry, &ListingEntry) -> Ordering + Send + Sync>;
+
+/// The entry display name function stored by [`ListingFileServer`].
+type DisplayNameFn = Arc<dyn Fn(&Path) -> Option<String> + Send + Sync>;
+
+/// The streamed listing entry renderer stored by [`ListingFileServer`].
+type StreamEntryRenderer = Arc<dyn Fn(&ListingEntry) -> String + Send + Sync>;
+
+/// The type-erased not-found page renderer stored by [`ListingFileServer`].
+type NotFoundRenderer =
+	Arc<dyn for<'r> Fn(&'r Request<'_>, &Path) -> response::Result<'static> + Send + Sync>;
+
+/// The type-erased listing error page renderer stored by
+/// [`ListingFileServer`].
+type ErrorRenderer =
+	Arc<dyn for<'r> Fn(&'r Request<'_>, &Path, Status) -> response::Result<'static> + Send + Sync>;
+
+/// The type-erased, fallible listing renderer stored by [`ListingFileServer`].
+type ListingRenderer =
+	Arc<dyn for<'r> Fn(&'r Request<'_>, Listing) -> response::Result<'static> + Send + Sync>;
+
+impl ListingFileServer {
 	/// The default rank use by `FileServer` routes.
 	const DEFAULT_RANK: isize = 10;
+	/// The default index file served when [`rocket::fs::Options::Index`] is
+	/// enabled.
+	const DEFAULT_INDEX_FILE: &'static str = "index.html";
+	/// The largest README file read for a listing, of 1 MiB.
+	const MAX_README_SIZE: u64 = 1024 * 1024;
+	/// The most directory levels collapsed into a single listing entry.
+	const MAX_COLLAPSED_DEPTH: usize = 32;
+	/// The size of the in-memory pipe between the writer of a streamed listing
+	/// and the response body.
+	const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+	/// The default number of directory levels searched beneath the requested
+	/// directory.
+	const DEFAULT_SEARCH_MAX_DEPTH: usize = 8;
+	/// The default maximum number of search results.
+	const DEFAULT_SEARCH_MAX_RESULTS: usize = 500;
+	/// The default number of directory levels walked beneath the requested
+	/// directory for its most recently modified files.
+	const DEFAULT_RECENT_MAX_DEPTH: usize = 8;
+	/// The default maximum number of files considered for the most recently
+	/// modified files.
+	const DEFAULT_RECENT_MAX_ENTRIES: usize = 10_000;
+	/// The default number of directory levels descended beneath each
+	/// subdirectory when computing its size.
+	const DEFAULT_DIR_SIZE_MAX_DEPTH: usize = 16;
 
 	/// Creates an instance of [`ListingFileServer`] with a path, no options
 	/// enabled (different from [`rocket::fs::FileServer`]), and a
@@ -52,13 +246,42 @@ impl<R: 'static + Fn(String, Vec<String>) -> Template + Send + Sync + Clone> Lis
 	/// using this type over [`rocket::fs::FileServer`], directory listing is
 	/// the expected default behaviour.
 	///
-	/// The template renderer receives a list of filenames found within the
-	/// directory, expected to be used in relative links.
+	/// The template renderer receives a [`Listing`] of the entries found within
+	/// the directory, each with a percent-encoded name for use in relative
+	/// links. It may return anything that implements [`Responder`], such as a
+	/// `rocket_dyn_templates::Template`:
+	///
+	/// ```no_run
+	/// use listing_file_server::ListingFileServer;
+	/// use rocket::{Build, Rocket};
+	/// use rocket_dyn_templates::Template;
+	///
+	/// fn rocket() -> Rocket<Build> {
+	/// 	rocket::build()
+	/// 		.mount(
+	/// 			"/",
+	/// 			ListingFileServer::from("static", |listing| Template::render("listing", &listing)),
+	/// 		)
+	/// 		.attach(Template::fairing())
+	/// }
+	/// ```
+	///
+	/// Or raw HTML, built without a template engine:
+	///
+	/// ```no_run
+	/// use listing_file_server::ListingFileServer;
+	/// use rocket::response::content::Html;
+	///
+	/// let server = ListingFileServer::from("static", |listing| {
+	/// 	Html(format!("{} entries", listing.entries.len()))
+	/// });
+	/// ```
 	#[track_caller]
-	pub fn from<P>(path: P, template_renderer: R) -> Self
+	pub fn from<P, R, T>(path: P, template_renderer: R) -> Self
 	where
 		P: AsRef<Path>,
-		R: 'static + Fn(String, Vec<String>) -> Template + Send + Sync + Clone,
+		R: 'static + Fn(Listing) -> T + Send + Sync,
+		T: for<'r> Responder<'r, 'static>,
 	{
 		ListingFileServer::new(path, Options::None, template_renderer)
 	}
@@ -66,19 +289,225 @@ impl<R: 'static + Fn(String, Vec<String>) -> Template + Send + Sync + Clone> Lis
 	/// Creates an instance of [`ListingFileServer`] with a path, options, and a
 	/// template-rendering function.
 	///
-	/// The template renderer receives a list of filenames found within the
-	/// directory, expected to be used in relative links.
+	/// The template renderer receives a [`Listing`] of the entries found within
+	/// the directory, each with a percent-encoded name for use in relative
+	/// links.
+	///
+	/// # Panics
+	///
+	/// Panics if `path` is not a directory.
+	#[track_caller]
+	pub fn new<P, R, T>(path: P, options: Options, template_renderer: R) -> Self
+	where
+		P: AsRef<Path>,
+		R: 'static + Fn(Listing) -> T + Send + Sync,
+		T: for<'r> Responder<'r, 'static>,
+	{
+		ListingFileServer::new_fallible(path, options, move |listing| {
+			Ok(template_renderer(listing))
+		})
+	}
+
+	/// Creates an instance of [`ListingFileServer`] with a path, options, and a
+	/// fallible template-rendering function.
+	///
+	/// This is the same as [`ListingFileServer::new`], except that the template
+	/// renderer may return an error [`Status`] instead of a response, in which
+	/// case the request fails with that status, or the
+	/// [`ListingFileServer::error_template`] page is served with it.
+	///
+	/// # Panics
+	///
+	/// Panics if `path` is not a directory.
+	#[track_caller]
+	pub fn new_fallible<P, R, T>(path: P, options: Options, template_renderer: R) -> Self
+	where
+		P: AsRef<Path>,
+		R: 'static + Fn(Listing) -> Result<T, Status> + Send + Sync,
+		T: for<'r> Responder<'r, 'static>,
+	{
+		ListingFileServer::with_renderer(
+			vec![path.as_ref().into()],
+			options,
+			Some(Arc::new(move |req, listing| {
+				template_renderer(listing)?.respond_to(req)
+			})),
+		)
+	}
+
+	/// Creates an instance of [`ListingFileServer`] with a path, options, a
+	/// context, and a template-rendering function that receives it.
+	///
+	/// This is the same as [`ListingFileServer::new`], except that a clone of
+	/// `context` is passed to the template renderer alongside each [`Listing`].
+	/// This keeps app-level values, such as a site title or theme, apart from
+	/// the per-request listing. Values that change while serving can be shared
+	/// through a context such as an `Arc<RwLock<_>>`.
+	///
+	/// # Panics
+	///
+	/// Panics if `path` is not a directory.
+	#[track_caller]
+	pub fn new_with_context<P, C, R, T>(
+		path: P,
+		options: Options,
+		context: C,
+		template_renderer: R,
+	) -> Self
+	where
+		P: AsRef<Path>,
+		C: 'static + Clone + Send + Sync,
+		R: 'static + Fn(Listing, C) -> T + Send + Sync,
+		T: for<'r> Responder<'r, 'static>,
+	{
+		ListingFileServer::new(path, options, move |listing| {
+			template_renderer(listing, context.clone())
+		})
+	}
+
+	/// Creates an instance of [`ListingFileServer`] with a path, no options
+	/// enabled, and a built-in HTML renderer.
+	///
+	/// This requires no templates to be set up, and produces a simple,
+	/// self-contained listing page. Use one of the other constructors to supply
+	/// a custom template renderer instead.
+	///
+	/// # Panics
+	///
+	/// Panics if `path` is not a directory.
 	#[track_caller]
-	pub fn new<P>(path: P, options: Options, template_renderer: R) -> Self
+	pub fn from_path<P>(path: P) -> Self
+	where
+		P: AsRef<Path>,
+	{
+		ListingFileServer::with_renderer(vec![path.as_ref().into()], Options::None, None)
+	}
+
+	/// Attempts to create an instance of [`ListingFileServer`] with a path,
+	/// options, and a template-rendering function.
+	///
+	/// This is the same as [`ListingFileServer::new`], except that it returns
+	/// an error instead of panicking if `path` is not a directory.
+	///
+	/// # Errors
+	///
+	/// Returns an error of kind [`io::ErrorKind::NotFound`] if `path` doesn't
+	/// exist, or of kind [`io::ErrorKind::NotADirectory`] if it exists but
+	/// isn't a directory. Any other error encountered while reading the
+	/// metadata of `path` is returned as-is.
+	pub fn try_new<P, R, T>(path: P, options: Options, template_renderer: R) -> io::Result<Self>
 	where
 		P: AsRef<Path>,
-		R: 'static + Fn(String, Vec<String>) -> Template + Send + Sync + Clone,
+		R: 'static + Fn(Listing) -> T + Send + Sync,
+		T: for<'r> Responder<'r, 'static>,
+	{
+		ListingFileServer::try_with_renderer(
+			vec![path.as_ref().into()],
+			options,
+			Some(Arc::new(move |req, listing| {
+				template_renderer(listing).respond_to(req)
+			})),
+		)
+	}
+
+	/// Attempts to create an instance of [`ListingFileServer`] from an
+	/// already-open directory, with options and a template-rendering function.
+	///
+	/// Every path is resolved through `dir` rather than through the path it was
+	/// opened with, so the root can't be swapped out from underneath the
+	/// server by renaming or replacing it after it's been checked. The
+	/// directory is held open for as long as the server or any of its routes
+	/// exist.
+	///
+	/// This is only available on Linux, since rather than opening each path
+	/// relative to `dir` with `openat`, paths are joined onto the magic link
+	/// `/proc/self/fd/N` of its file descriptor, which the kernel resolves to
+	/// the open directory. This requires `/proc` to be mounted and readable
+	/// by the process, which isn't the case in some chroots and containers.
+	/// It only pins the root itself: the paths beneath it are still resolved
+	/// one component at a time as usual, so a directory within the root that
+	/// is swapped for a symlink is followed like any other, subject to
+	/// [`ListingFileServer::follow_symlinks`].
+	///
+	/// # Errors
+	///
+	/// Returns an error of kind [`io::ErrorKind::NotADirectory`] if `dir`
+	/// isn't a directory, or of kind [`io::ErrorKind::NotFound`] if `/proc`
+	/// isn't mounted. Any other error encountered while reading the metadata
+	/// of `dir` is returned as-is.
+	#[cfg(target_os = "linux")]
+	pub fn try_from_dir<R, T>(
+		dir: fs::File,
+		options: Options,
+		template_renderer: R,
+	) -> io::Result<Self>
+	where
+		R: 'static + Fn(Listing) -> T + Send + Sync,
+		T: for<'r> Responder<'r, 'static>,
+	{
+		use std::os::fd::AsRawFd;
+
+		if !dir.metadata()?.is_dir() {
+			return Err(io::Error::new(
+				io::ErrorKind::NotADirectory,
+				"the handle is not a directory",
+			));
+		}
+		let root = PathBuf::from(format!("/proc/self/fd/{}", dir.as_raw_fd()));
+		let mut server = ListingFileServer::try_with_renderer(
+			vec![root],
+			options,
+			Some(Arc::new(move |req, listing| {
+				template_renderer(listing).respond_to(req)
+			})),
+		)?;
+		server.root_handle = Some(Arc::new(dir));
+		Ok(server)
+	}
+
+	/// Creates an instance of [`ListingFileServer`] that overlays several root
+	/// directories, with options and a template-rendering function.
+	///
+	/// Files are served from the first root (in order) that has them.
+	/// Directory listings merge the entries of the directory from every root
+	/// that has it, de-duplicated by name with the first root winning. The
+	/// exception is that a name is treated as a directory if it's a directory
+	/// in any root.
+	///
+	/// # Panics
+	///
+	/// Panics if `roots` is empty, or if any of them is not a directory.
+	#[track_caller]
+	pub fn new_overlay<R, T>(roots: Vec<PathBuf>, options: Options, template_renderer: R) -> Self
+	where
+		R: 'static + Fn(Listing) -> T + Send + Sync,
+		T: for<'r> Responder<'r, 'static>,
 	{
+		ListingFileServer::with_renderer(
+			roots,
+			options,
+			Some(Arc::new(move |req, listing| {
+				template_renderer(listing).respond_to(req)
+			})),
+		)
+	}
+
+	/// Creates an instance of [`ListingFileServer`] with an already type-erased
+	/// renderer, or the built-in HTML renderer if `renderer` is [`None`].
+	///
+	/// # Panics
+	///
+	/// Panics if `roots` is empty, or if any of them is not a directory.
+	#[track_caller]
+	fn with_renderer(
+		roots: Vec<PathBuf>,
+		options: Options,
+		renderer: Option<ListingRenderer>,
+	) -> Self {
 		use rocket::yansi::Paint;
 
-		let path = path.as_ref();
-		if !path.is_dir() {
-			let path = path.display();
+		if let Some(root) = roots.iter().find(|root| !root.is_dir()) {
+			let path = root.display();
 			error!(
 				"ListingFileServer path '{}' is not a directory.",
 				Paint::white(path)
@@ -86,118 +515,3387 @@ impl<R: 'static + Fn(String, Vec<String>) -> Template + Send + Sync + Clone> Lis
 			warn_!("Aborting early to prevent inevitable handler failure.");
 			panic!("bad ListingFileServer path: refusing to continue");
 		}
+		let Ok(server) = ListingFileServer::try_with_renderer(roots, options, renderer) else {
+			error!("ListingFileServer requires at least one path.");
+			warn_!("Aborting early to prevent inevitable handler failure.");
+			panic!("bad ListingFileServer path: refusing to continue");
+		};
+
+		server
+	}
+
+	/// Creates an instance of [`ListingFileServer`] with an already type-erased
+	/// renderer, or the built-in HTML renderer if `renderer` is [`None`],
+	/// verifying that every root is a directory.
+	fn try_with_renderer(
+		roots: Vec<PathBuf>,
+		options: Options,
+		renderer: Option<ListingRenderer>,
+	) -> io::Result<Self> {
+		if roots.is_empty() {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"at least one root directory is required",
+			));
+		}
+		for root in &roots {
+			if !fs::metadata(root)?.is_dir() {
+				return Err(io::Error::new(
+					io::ErrorKind::NotADirectory,
+					format!("'{}' is not a directory", root.display()),
+				));
+			}
+		}
 
-		ListingFileServer {
-			root: path.into(),
+		Ok(ListingFileServer {
+			roots,
+			root_handle: None,
 			options,
 			rank: Self::DEFAULT_RANK,
-			template_renderer,
-		}
+			route_base: String::new(),
+			renderer,
+			inline_css: None,
+			sort_by: SortBy::default(),
+			sort_order: SortOrder::default(),
+			sort_options: SortOptions::default(),
+			comparator: None,
+			group_by_extension: false,
+			index_files: vec![Self::DEFAULT_INDEX_FILE.to_owned()],
+			index_at_root_only: false,
+			show_parent_link: false,
+			follow_symlinks: true,
+			follow_file_symlinks: true,
+			follow_dir_symlinks: true,
+			skip_non_utf8_names: false,
+			enable_json: false,
+			enable_text: false,
+			default_listing_format: ListingFormat::default(),
+			enable_archive_download: false,
+			cache_listings: false,
+			render_cache: None,
+			hide_dotfiles_in_listing: false,
+			exclude_patterns: Vec::new(),
+			include_patterns: Vec::new(),
+			always_include_dirs: true,
+			hidden_patterns: Vec::new(),
+			deny_excluded: false,
+			page_size: None,
+			directory_config: false,
+			access_filter: None,
+			forbid_filtered: false,
+			enable_search: false,
+			search_max_depth: Self::DEFAULT_SEARCH_MAX_DEPTH,
+			search_max_results: Self::DEFAULT_SEARCH_MAX_RESULTS,
+			enable_recent: false,
+			recent_max_depth: Self::DEFAULT_RECENT_MAX_DEPTH,
+			recent_max_entries: Self::DEFAULT_RECENT_MAX_ENTRIES,
+			file_cache_control: None,
+			listing_cache_control: None,
+			enable_feed: false,
+			enable_playlist: false,
+			playlist_extensions: DEFAULT_PLAYLIST_EXTENSIONS
+				.iter()
+				.map(|&extension| extension.to_owned())
+				.collect(),
+			readme_files: Vec::new(),
+			entry_filter: EntryFilter::default(),
+			url_prefix: None,
+			public_origin: None,
+			show_symlink_targets: false,
+			compression: false,
+			precompressed: false,
+			content_type_overrides: HashMap::new(),
+			force_download_extensions: Vec::new(),
+			max_file_size: None,
+			oversized_status: None,
+			dotfile_denied_status: None,
+			on_access: None,
+			max_listing_entries: None,
+			listing_overflow_status: None,
+			noindex_marker: None,
+			max_path_depth: None,
+			path_depth_status: None,
+			noindex_status: None,
+			vanished_status: None,
+			dir_suffix: Some('/'),
+			display_name_fn: None,
+			size_units: SizeUnits::default(),
+			not_found_renderer: None,
+			error_renderer: None,
+			fallback: None,
+			trailing_slash: TrailingSlash::default(),
+			normalize_redirect_permanent: true,
+			stream_listings: false,
+			stream_entry_renderer: None,
+			collapse_single_dirs: false,
+			compute_dir_sizes: false,
+			dir_size_max_depth: Self::DEFAULT_DIR_SIZE_MAX_DEPTH,
+			cors: None,
+			#[cfg(feature = "image")]
+			enable_thumbnails: false,
+			#[cfg(feature = "image")]
+			thumbnail_cache_dir: None,
+		})
 	}
 
 	/// Sets the rank for generated routes to `rank`.
+	#[must_use]
 	pub fn rank(mut self, rank: isize) -> Self {
 		self.rank = rank;
 		self
 	}
-}
 
-impl<R: 'static + Fn(String, Vec<String>) -> Template + Send + Sync + Clone> Into<Vec<Route>>
-	for ListingFileServer<R>
-{
-	fn into(self) -> Vec<Route> {
-		let source = figment::Source::File(self.root.clone());
-		let mut route = Route::ranked(self.rank, Method::Get, "/<path..>", self);
-		route.name = Some(format!("ListingFileServer: {}/", source).into());
-		vec![route]
+	/// Sets the path, relative to the mount point, that generated routes serve
+	/// the server root at.
+	///
+	/// For example, a server with a route base of `/downloads` that's mounted
+	/// at `/files` serves its root at `/files/downloads/`. This makes it
+	/// possible to serve the same tree at several paths from one mount point.
+	///
+	/// The default is `/`, serving the root at the mount point itself.
+	#[must_use]
+	pub fn route_base<S: AsRef<str>>(mut self, route_base: S) -> Self {
+		self.route_base = route_base
+			.as_ref()
+			.split('/')
+			.filter(|segment| !segment.is_empty())
+			.fold(String::new(), |mut base, segment| {
+				base.push('/');
+				base.push_str(segment);
+				base
+			});
+		self
 	}
-}
 
-#[async_trait]
-impl<R: 'static + Fn(String, Vec<String>) -> Template + Send + Sync + Clone> Handler
-	for ListingFileServer<R>
-{
-	async fn handle<'r>(&self, req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r> {
-		use rocket::http::uri::fmt::Path;
+	/// Creates a fairing that mounts the server's routes at `base` when the
+	/// Rocket instance ignites, so it can be attached in one call:
+	///
+	/// ```no_run
+	/// use listing_file_server::ListingFileServer;
+	/// use rocket::{Build, Rocket};
+	///
+	/// fn rocket() -> Rocket<Build> {
+	/// 	rocket::build().attach(ListingFileServer::from_path("static").fairing("/files"))
+	/// }
+	/// ```
+	///
+	/// This is the same as mounting the server with
+	/// `.mount(base, server)`, which remains available for more control over
+	/// the routes.
+	///
+	/// # Panics
+	///
+	/// Igniting panics if `base` isn't a valid origin URI, as
+	/// [`rocket::Rocket::mount`] does.
+	#[must_use]
+	pub fn fairing<B: Into<String>>(self, base: B) -> AdHoc {
+		let base = base.into();
+		AdHoc::on_ignite("ListingFileServer", |rocket| async move {
+			rocket.mount(base, self)
+		})
+	}
 
-		// Get the segments as a `PathBuf`, allowing dotfiles requested.
-		let options = self.options;
-		let allow_dotfiles = options.contains(Options::DotFiles);
-		let req_path = req
-			.segments::<Segments<'_, Path>>(0..)
-			.ok()
-			.and_then(|segments| segments.to_path_buf(allow_dotfiles).ok());
-		let path = req_path.clone().map(|path| self.root.join(path));
+	/// Replaces the options the server was created with by `options`.
+	#[must_use]
+	pub fn options(mut self, options: Options) -> Self {
+		self.options = options;
+		self
+	}
 
-		match path {
-			Some(p) if p.is_dir() => {
-				// Normalize '/a/b/foo' to '/a/b/foo/'.
-				if options.contains(Options::NormalizeDirs) && !req.uri().path().ends_with('/') {
-					let normal = req
-						.uri()
-						.map_path(|p| format!("{}/", p))
-						.expect("adding a trailing slash to a known good path => valid path")
-						.into_owned();
+	/// Enables `option` in addition to the server's current options.
+	///
+	/// `option` may combine several options.
+	#[must_use]
+	pub fn add_option(mut self, option: Options) -> Self {
+		self.options = self.options | option;
+		self
+	}
 
-					return Outcome::from_or_forward(req, data, Redirect::permanent(normal));
-				}
+	/// Disables `option`, keeping the server's other options.
+	///
+	/// `option` may combine several options.
+	#[must_use]
+	pub fn remove_option(mut self, option: Options) -> Self {
+		// `Options` has no way to clear flags, so rebuild it from the known ones
+		self.options = [Options::Index, Options::DotFiles, Options::NormalizeDirs]
+			.into_iter()
+			.filter(|&known| self.options.contains(known) && !option.contains(known))
+			.fold(Options::None, |options, known| options | known);
+		self
+	}
 
-				if options.contains(Options::Index) {
-					let index = NamedFile::open(p.join("index.html")).await.ok();
-					if index.is_some() {
-						return Outcome::from(req, index);
-					}
-				}
+	/// Sets the field and order that directory listings are sorted by.
+	///
+	/// The default is [`SortBy::Name`] in [`SortOrder::Ascending`] order.
+	///
+	/// Clients may override this per-request with the `sort` and `order` query
+	/// parameters (for example, `?sort=size&order=desc`). Absent or invalid
+	/// parameters fall back to the values set here.
+	#[must_use]
+	pub fn sort(mut self, by: SortBy, order: SortOrder) -> Self {
+		self.sort_by = by;
+		self.sort_order = order;
+		self
+	}
 
-				match read_dir(&p) {
-					// Directory
-					Ok(dir_entries) => {
-						// Prepare the directory path string
-						let mut directory = String::from('/');
-						directory.push_str(
-							req_path
-								.unwrap()
-								.into_os_string()
-								.into_string()
-								.expect("Unable to convert directory path from OS string")
-								.replace('\\', "/")
-								.as_str(),
-						);
-						if !directory.ends_with('/') {
-							directory.push('/');
-						}
-						// Prepare the directory entries list
-						let mut entry_list = dir_entries
-							.filter(|res| res.is_ok())
-							.map(|res| {
-								let mut entry = res
-									.unwrap()
-									.file_name()
-									.into_string()
-									.expect("Unable to convert directory entry from OS string");
-								let is_dir = p.join(&entry).is_dir();
-								if is_dir {
-									entry.push('/');
-								}
-								(Reverse(is_dir), entry)
-							})
-							.collect::<Vec<_>>();
-						entry_list.sort_unstable();
-						// Render the template
-						Outcome::from(
-							req,
-							(self.template_renderer)(
-								directory,
-								entry_list.drain(..).map(|e| e.1).collect::<Vec<_>>(),
-							),
-						)
-					}
-					// File
-					_ => Outcome::forward(data),
-				}
-			}
-			Some(p) => Outcome::from_or_forward(req, data, NamedFile::open(p).await.ok()),
-			None => Outcome::forward(data),
+	/// Sets whether directories are listed before all files, regardless of the
+	/// sort order.
+	///
+	/// If disabled, directories and files are sorted together, so that
+	/// `apple/`, `banana.txt`, and `cherry/` interleave by name. Directories
+	/// are still marked as such in listings either way. The parent directory
+	/// link, if shown, is always listed first.
+	///
+	/// This is enabled by default.
+	#[must_use]
+	pub fn dirs_first(mut self, dirs_first: bool) -> Self {
+		self.sort_options.dirs_first = dirs_first;
+		self
+	}
+
+	/// Sets whether runs of digits within names are compared numerically when
+	/// sorting, so that `file2.txt` comes before `file10.txt`.
+	///
+	/// This is disabled by default, comparing names character by character.
+	#[must_use]
+	pub fn natural_sort(mut self, natural_sort: bool) -> Self {
+		self.sort_options.natural = natural_sort;
+		self
+	}
+
+	/// Sets whether case is ignored when comparing names for sorting, so that
+	/// `Apple`, `banana`, and `Cherry` sort in that order.
+	///
+	/// Names that differ only in case are still ordered consistently. This is
+	/// disabled by default, so all uppercase letters sort before lowercase.
+	#[must_use]
+	pub fn case_insensitive_sort(mut self, case_insensitive_sort: bool) -> Self {
+		self.sort_options.case_insensitive = case_insensitive_sort;
+		self
+	}
+
+	/// Sets a function that orders listing entries, overriding the built-in
+	/// sort entirely.
+	///
+	/// The entries passed to `comparator` have their metadata already read,
+	/// so it can order them by [`ListingEntry::size`] or
+	/// [`ListingEntry::modified`] as well as by name, such as to pin certain
+	/// files to the top. The `sort` and `order` query parameters, and the
+	/// [`ListingFileServer::sort`], [`ListingFileServer::dirs_first`], and
+	/// related settings, are ignored while it's set, except for listings of
+	/// the most recently modified files. The `../` entry is always listed
+	/// first.
+	///
+	/// By default, the built-in sort is used.
+	#[must_use]
+	pub fn sort_by<F>(mut self, comparator: F) -> Self
+	where
+		F: 'static + Fn(&ListingEntry, &ListingEntry) -> Ordering + Send + Sync,
+	{
+		self.comparator = Some(Arc::new(comparator));
+		self
+	}
+
+	/// Sets whether listing entries are also grouped by file extension, with
+	/// directories in a group of their own, as described for
+	/// [`Listing::groups`].
+	///
+	/// The default HTML listing shows each group under a heading. This is
+	/// disabled by default.
+	#[must_use]
+	pub fn group_by_extension(mut self, group_by_extension: bool) -> Self {
+		self.group_by_extension = group_by_extension;
+		self
+	}
+
+	/// Sets the locale whose collation rules are used to compare names for
+	/// sorting, such as `fr` or `sv`, so that accented letters sort alongside
+	/// their base letters.
+	///
+	/// This composes with [`Self::natural_sort`] and
+	/// [`Self::case_insensitive_sort`]. This is disabled by default, comparing
+	/// names by their bytes.
+	///
+	/// # Panics
+	///
+	/// Panics if `locale` isn't a valid locale identifier, or if no collation
+	/// data is available for it.
+	#[cfg(feature = "collation")]
+	#[must_use]
+	#[track_caller]
+	pub fn collation<S: AsRef<str>>(mut self, locale: S) -> Self {
+		use icu_collator::{options::CollatorOptions, CollatorBorrowed};
+		use icu_locale_core::Locale;
+		use rocket::yansi::Paint;
+
+		let locale = locale.as_ref();
+		let collator = Locale::try_from_str(locale)
+			.map_err(|e| e.to_string())
+			.and_then(|parsed| {
+				CollatorBorrowed::try_new((&parsed).into(), CollatorOptions::default())
+					.map_err(|e| e.to_string())
+			})
+			.unwrap_or_else(|e| {
+				error!(
+					"ListingFileServer collation locale '{}' is invalid: {}",
+					Paint::white(locale),
+					e
+				);
+				warn_!("Aborting early to prevent inevitable handler failure.");
+				panic!("bad ListingFileServer collation locale: refusing to continue");
+			});
+		self.sort_options.collator = Some(Arc::new(collator));
+		self
+	}
+
+	/// Sets the candidate index filenames tried, in order, for directory
+	/// requests when [`rocket::fs::Options::Index`] is enabled.
+	///
+	/// The first candidate that exists is served. If the list is empty, the
+	/// directory listing is always shown, as if the option weren't enabled.
+	///
+	/// The default is `index.html`.
+	#[must_use]
+	pub fn index_files<S: AsRef<str>>(mut self, index_files: &[S]) -> Self {
+		self.index_files = index_files
+			.iter()
+			.map(|file| file.as_ref().to_owned())
+			.collect();
+		self
+	}
+
+	/// Sets how the trailing slashes of requested URLs are normalized with
+	/// redirects.
+	///
+	/// [`rocket::fs::Options::NormalizeDirs`] still adds trailing slashes to
+	/// directory URLs if enabled, unless this is set to
+	/// [`TrailingSlash::StripFromAll`].
+	///
+	/// The default is [`TrailingSlash::StripFromFiles`].
+	#[must_use]
+	pub fn trailing_slash(mut self, trailing_slash: TrailingSlash) -> Self {
+		self.trailing_slash = trailing_slash;
+		self
+	}
+
+	/// Sets whether the redirects that normalize trailing slashes are
+	/// permanent.
+	///
+	/// Browsers cache permanent redirects aggressively, which is hard to undo
+	/// if the server's layout changes, such as during development or with
+	/// dynamic mounts. If disabled, both files and directories are redirected
+	/// with a [`Status::TemporaryRedirect`] instead. This applies to
+	/// [`rocket::fs::Options::NormalizeDirs`] as well as the
+	/// [`ListingFileServer::trailing_slash`] policy.
+	///
+	/// This is enabled by default.
+	#[must_use]
+	pub fn normalize_redirect_permanent(mut self, normalize_redirect_permanent: bool) -> Self {
+		self.normalize_redirect_permanent = normalize_redirect_permanent;
+		self
+	}
+
+	/// Sets whether chains of directories that each contain nothing but a
+	/// single subdirectory are collapsed into one listing entry.
+	///
+	/// For example, if `com/` contains only `example/`, which contains only
+	/// `foo/`, the listing shows a single `com/example/foo/` entry linking
+	/// straight to `foo/`. Collapsing stops at the first directory with any
+	/// other entries, and after 32 levels. Entries left out of listings, such
+	/// as those matching the [`ListingFileServer::exclude_patterns`], aren't
+	/// counted.
+	///
+	/// Each directory entry of a listing costs an extra directory read, and
+	/// search results and streamed listings aren't collapsed.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn collapse_single_dirs(mut self, collapse_single_dirs: bool) -> Self {
+		self.collapse_single_dirs = collapse_single_dirs;
+		self
+	}
+
+	/// Sets whether the size of each subdirectory in a listing is computed, as
+	/// the total size of the files beneath it, and passed to the renderer as
+	/// [`ListingEntry::size`].
+	///
+	/// **This is expensive**: every listing walks the whole subtree beneath
+	/// each of its subdirectories, which for a directory near the root can mean
+	/// most of the filesystem. The walk is bounded by
+	/// [`ListingFileServer::dir_size_max_depth`], and entries are only counted
+	/// if they'd be listed. Files shadowed by the same file in an earlier root
+	/// are only counted once. Symlinks are never followed or counted, so
+	/// symlinked subdirectories have no size. Search results and streamed
+	/// listings don't have directory sizes.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn compute_dir_sizes(mut self, compute_dir_sizes: bool) -> Self {
+		self.compute_dir_sizes = compute_dir_sizes;
+		self
+	}
+
+	/// Sets the maximum number of directory levels descended beneath each
+	/// subdirectory when computing its size for
+	/// [`ListingFileServer::compute_dir_sizes`].
+	///
+	/// A `max_depth` of 0 only counts the files directly within each
+	/// subdirectory. Files deeper than this aren't counted.
+	///
+	/// The default is 16.
+	#[must_use]
+	pub fn dir_size_max_depth(mut self, max_depth: usize) -> Self {
+		self.dir_size_max_depth = max_depth;
+		self
+	}
+
+	/// Sets whether index files are only served for the root directory, so
+	/// that every subdirectory shows its listing.
+	///
+	/// This only has an effect when [`rocket::fs::Options::Index`] is enabled,
+	/// and also applies to index files set with
+	/// [`ListingFileServer::directory_config`].
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn index_at_root_only(mut self, index_at_root_only: bool) -> Self {
+		self.index_at_root_only = index_at_root_only;
+		self
+	}
+
+	/// Sets whether a `../` entry linking to the parent directory is placed at
+	/// the start of directory listings.
+	///
+	/// The entry is never added to the listing of the root directory.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn show_parent_link(mut self, show_parent_link: bool) -> Self {
+		self.show_parent_link = show_parent_link;
+		self
+	}
+
+	/// Sets the character appended to the names of directories in listings,
+	/// or [`None`] to leave names as-is.
+	///
+	/// This only affects [`ListingEntry::name`], so renderers that decorate
+	/// directories themselves can rely on [`ListingEntry::is_dir`] instead.
+	/// Directory hrefs always end with a `/`.
+	///
+	/// The default is `/`.
+	#[must_use]
+	pub fn dir_suffix(mut self, dir_suffix: Option<char>) -> Self {
+		self.dir_suffix = dir_suffix;
+		self
+	}
+
+	/// Sets a function that gives entries friendly labels to be shown in
+	/// place of their names, such as from a manifest of files with generated
+	/// names.
+	///
+	/// The function is called with the path of each listed entry, relative to
+	/// the server root, and its label is passed to the renderer as
+	/// [`ListingEntry::display_name`]. Returning [`None`] keeps the entry's own
+	/// name. Either way, [`ListingEntry::name`] and [`ListingEntry::href`]
+	/// keep the real name, so links still point at the real files.
+	///
+	/// By default, entries are shown by their own names.
+	#[must_use]
+	pub fn display_name_fn<F>(mut self, display_name_fn: F) -> Self
+	where
+		F: 'static + Fn(&Path) -> Option<String> + Send + Sync,
+	{
+		self.display_name_fn = Some(Arc::new(display_name_fn));
+		self
+	}
+
+	/// Sets the units that entry sizes are formatted in for
+	/// [`ListingEntry::human_size`], such as `1.2 GiB` or `1.3 GB`.
+	///
+	/// The raw [`ListingEntry::size`] in bytes is passed along either way. The
+	/// default is [`SizeUnits::Binary`].
+	#[must_use]
+	pub fn size_units(mut self, size_units: SizeUnits) -> Self {
+		self.size_units = size_units;
+		self
+	}
+
+	/// Sets whether symlinks that resolve to a location outside of the server
+	/// root are followed.
+	///
+	/// Every requested path is canonicalized and checked to still be within
+	/// the canonicalized root before anything is served or listed, and
+	/// requests that escape the root are forwarded, as if the path didn't
+	/// exist. When this is enabled, the only exception is a path that escapes
+	/// through a symlink beneath the root. When disabled, there are no
+	/// exceptions. Symlinks that resolve within the root are unaffected.
+	///
+	/// Listings follow symlinks the same way, so a symlink that escapes the
+	/// root is listed as the link itself rather than as its target.
+	///
+	/// This is enabled by default, but it's strongly recommended to disable it
+	/// when the server is publicly accessible.
+	#[must_use]
+	pub fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+		self.follow_symlinks = follow_symlinks;
+		self
+	}
+
+	/// Sets whether symlinks to files are followed, wherever they resolve.
+	///
+	/// When disabled, requests for a symlink to a file are forwarded, as if it
+	/// didn't exist, and listings show the link itself rather than its target.
+	/// A symlink is only followed if this and
+	/// [`ListingFileServer::follow_symlinks`] both allow it.
+	///
+	/// This is enabled by default.
+	#[must_use]
+	pub fn follow_file_symlinks(mut self, follow_file_symlinks: bool) -> Self {
+		self.follow_file_symlinks = follow_file_symlinks;
+		self
+	}
+
+	/// Sets whether symlinks to directories are followed, wherever they
+	/// resolve.
+	///
+	/// When disabled, requests for anything at or beneath a symlink to a
+	/// directory are forwarded, as if it didn't exist, and listings show the
+	/// link itself rather than its target, so searches, archives, and other
+	/// walks of the subtree don't descend into it. A symlink is only followed
+	/// if this and [`ListingFileServer::follow_symlinks`] both allow it.
+	///
+	/// A symlink to one of its own ancestors makes the tree endlessly deep
+	/// when followed. Walks of the subtree are still bounded by their depth
+	/// limits, and archives skip directories they've already included, but
+	/// every path through such a loop is a valid URL that crawlers may follow
+	/// indefinitely. Disabling this rules such loops out, and keeps the
+	/// served tree to the one laid out on disk.
+	///
+	/// This is enabled by default.
+	#[must_use]
+	pub fn follow_dir_symlinks(mut self, follow_dir_symlinks: bool) -> Self {
+		self.follow_dir_symlinks = follow_dir_symlinks;
+		self
+	}
+
+	/// Sets whether directory entries with names that aren't valid UTF-8 are
+	/// left out of listings.
+	///
+	/// When disabled, such names are converted lossily, replacing invalid
+	/// sequences with `U+FFFD REPLACEMENT CHARACTER`. Links to these entries
+	/// won't resolve, as they're built from the converted name. On Unix, such
+	/// an entry can still be requested with its raw bytes percent-encoded,
+	/// such as `/caf%E9/`.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn skip_non_utf8_names(mut self, skip_non_utf8_names: bool) -> Self {
+		self.skip_non_utf8_names = skip_non_utf8_names;
+		self
+	}
+
+	/// Sets whether directory listings are served as JSON to clients whose
+	/// `Accept` header prefers `application/json`, or that request it with the
+	/// `format=json` query parameter.
+	///
+	/// The template renderer isn't used for these requests. See [`Listing`] for
+	/// the shape of the JSON, and [`ListingFileServer::default_listing_format`]
+	/// for how the format is picked.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn enable_json(mut self, enable_json: bool) -> Self {
+		self.enable_json = enable_json;
+		self
+	}
+
+	/// Sets whether directory listings are served as plain text in the style
+	/// of `ls -l` to clients whose `Accept` header prefers `text/plain`, or
+	/// that request it with the `format=text` query parameter.
+	///
+	/// Each entry is listed on its own line, with its mode (on Unix), size,
+	/// modified time in UTC, and name, which is easy to read from a terminal
+	/// or a shell pipeline. The template renderer isn't used for these
+	/// requests. See [`ListingFileServer::default_listing_format`] for how the
+	/// format is picked.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn enable_text(mut self, enable_text: bool) -> Self {
+		self.enable_text = enable_text;
+		self
+	}
+
+	/// Sets the format directory listings are served in when the client
+	/// doesn't prefer any in particular.
+	///
+	/// The format of each listing is picked from HTML and whichever of
+	/// [`ListingFileServer::enable_json`], [`ListingFileServer::enable_text`],
+	/// [`ListingFileServer::enable_feed`], and
+	/// [`ListingFileServer::enable_playlist`] are enabled:
+	///
+	/// 1. A format requested with the `format` query parameter (`html`, `json`,
+	///    `text`, `atom`, or `m3u`), or with `feed=atom`, is always used.
+	/// 2. Otherwise, the `Accept` header is negotiated using its quality
+	///    values. Each format is weighted by the most specific media range that
+	///    matches it, and ties go to the more specific range, then the one
+	///    listed first. Formats with a quality of 0 aren't used.
+	/// 3. If there's no `Accept` header, or its preferred format is only
+	///    matched by `*/*`, this format is used.
+	///
+	/// Requests for formats that aren't enabled are ignored, and this falls
+	/// back to HTML if its format isn't enabled. Listings are sent with
+	/// `Vary: Accept` when more than one format is enabled.
+	///
+	/// The default is [`ListingFormat::Html`].
+	#[must_use]
+	pub fn default_listing_format(mut self, format: ListingFormat) -> Self {
+		self.default_listing_format = format;
+		self
+	}
+
+	/// Sets whether directories can be downloaded as an archive, using the
+	/// `download` query parameter: `?download=zip` for a zip archive, or
+	/// `?download=tar.gz` for a gzip-compressed tar archive that keeps file
+	/// permissions.
+	///
+	/// Archives are streamed to the client as they're written, and hold the
+	/// same entries as the listings of the directory and its subdirectories
+	/// would, so everything left out of listings is left out of archives too.
+	/// Dotfiles are additionally only included if
+	/// [`rocket::fs::Options::DotFiles`] is enabled.
+	///
+	/// Symlinks are followed, except that tar archives keep those with a
+	/// relative target that's archived as well as symlinks, so they don't
+	/// dangle once unpacked.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn enable_archive_download(mut self, enable_archive_download: bool) -> Self {
+		self.enable_archive_download = enable_archive_download;
+		self
+	}
+
+	/// Sets whether directory listings carry `ETag` and `Last-Modified`
+	/// headers, and respond to conditional requests with `304 Not Modified`.
+	///
+	/// The validators are derived from the latest modified time of the
+	/// directory and its entries, and the entries listed, so changes anywhere
+	/// deeper in the tree aren't detected. Each format, sort, and page of a
+	/// listing has its own entity tag.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn cache_listings(mut self, cache_listings: bool) -> Self {
+		self.cache_listings = cache_listings;
+		self
+	}
+
+	/// Sets the number of rendered listings kept in memory, so that listings
+	/// of unchanged directories aren't rendered again on every request.
+	///
+	/// This is a server-side cache, separate from the validators of
+	/// [`ListingFileServer::cache_listings`]. The least recently used render
+	/// is dropped once `capacity` are kept. Renders are kept per URL, sort, and
+	/// page, and are reused only while the modified time of the directory,
+	/// its title and README, and the names, sizes, and modified times of the
+	/// entries shown are unchanged. Other changes, such as to an entry's
+	/// permissions alone, aren't noticed.
+	///
+	/// Only the built-in HTML renderer is cached, since the output of a
+	/// template renderer may depend on more than the listing. Searches and
+	/// recent-file listings also aren't cached, and nothing is cached while an
+	/// [`ListingFileServer::access_filter`] or
+	/// [`ListingFileServer::display_name_fn`] is set, since what they show may
+	/// differ between requests. A `capacity` of 0 disables the cache, as it is
+	/// by default.
+	#[must_use]
+	pub fn render_cache(mut self, capacity: usize) -> Self {
+		self.render_cache = (capacity > 0).then(|| Arc::new(RenderCache::new(capacity)));
+		self
+	}
+
+	/// Sets whether the targets of symlinks are read and passed to the
+	/// renderer as [`ListingEntry::symlink_target`].
+	///
+	/// This is disabled by default, since targets can reveal paths outside of
+	/// the server root.
+	#[must_use]
+	pub fn show_symlink_targets(mut self, show_symlink_targets: bool) -> Self {
+		self.show_symlink_targets = show_symlink_targets;
+		self
+	}
+
+	/// Sets the status that requests for dotfiles fail with when
+	/// [`rocket::fs::Options::DotFiles`] isn't enabled, such as
+	/// [`Status::Forbidden`].
+	///
+	/// By default, such requests are forwarded, as if the dotfile didn't
+	/// exist, which avoids revealing whether it does.
+	#[must_use]
+	pub fn dotfile_denied_status(mut self, status: Status) -> Self {
+		self.dotfile_denied_status = Some(status);
+		self
+	}
+
+	/// Sets whether dotfiles are left out of directory listings.
+	///
+	/// This is independent of [`rocket::fs::Options::DotFiles`], which controls
+	/// whether dotfiles can be served. Hiding them from listings doesn't
+	/// prevent direct requests for them.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn hide_dotfiles_in_listing(mut self, hide_dotfiles_in_listing: bool) -> Self {
+		self.hide_dotfiles_in_listing = hide_dotfiles_in_listing;
+		self
+	}
+
+	/// Sets glob patterns for entries that are left out of directory listings
+	/// and archives, such as `*.tmp` or `node_modules`.
+	///
+	/// Patterns are matched against the name of each entry only, not its full
+	/// path, and apply to directories as well as files. Use
+	/// [`ListingFileServer::deny_excluded`] to also prevent excluded entries
+	/// from being served directly.
+	///
+	/// # Panics
+	///
+	/// Panics if any of the patterns is not a valid glob pattern.
+	#[must_use]
+	#[track_caller]
+	pub fn exclude_patterns<S: AsRef<str>>(mut self, exclude_patterns: &[S]) -> Self {
+		self.exclude_patterns = parse_patterns(exclude_patterns, "exclude");
+		self
+	}
+
+	/// Sets glob patterns for the only files that are shown in directory
+	/// listings and archives, such as `*.pdf` or `*.epub`.
+	///
+	/// Patterns are matched against the name of each entry only, as with
+	/// [`ListingFileServer::exclude_patterns`], which take precedence over
+	/// these. Directories are always shown, so that the files within them can
+	/// be reached, unless [`ListingFileServer::always_include_dirs`] is
+	/// disabled. Use [`ListingFileServer::deny_excluded`] to also prevent
+	/// other files from being served directly.
+	///
+	/// The default is empty, so every entry is shown.
+	///
+	/// # Panics
+	///
+	/// Panics if any of the patterns is not a valid glob pattern.
+	#[must_use]
+	#[track_caller]
+	pub fn include_patterns<S: AsRef<str>>(mut self, include_patterns: &[S]) -> Self {
+		self.include_patterns = parse_patterns(include_patterns, "include");
+		self
+	}
+
+	/// Sets whether directories are shown regardless of the
+	/// [`ListingFileServer::include_patterns`].
+	///
+	/// If disabled, directories are only shown if their names match one of the
+	/// patterns, like files. This has no effect if there are no include
+	/// patterns.
+	///
+	/// This is enabled by default.
+	#[must_use]
+	pub fn always_include_dirs(mut self, always_include_dirs: bool) -> Self {
+		self.always_include_dirs = always_include_dirs;
+		self
+	}
+
+	/// Sets glob patterns for entries that are flagged as hidden in directory
+	/// listings, in addition to dotfiles.
+	///
+	/// Hidden entries are still listed, with [`ListingEntry::is_hidden`] set so
+	/// that renderers can set them apart. This is independent of whether
+	/// they're served. As with [`ListingFileServer::exclude_patterns`],
+	/// patterns are matched against the name of each entry only.
+	///
+	/// The default is empty, so only dotfiles are flagged.
+	///
+	/// # Panics
+	///
+	/// Panics if any of the patterns is not a valid glob pattern.
+	#[must_use]
+	#[track_caller]
+	pub fn hidden_patterns<S: AsRef<str>>(mut self, hidden_patterns: &[S]) -> Self {
+		self.hidden_patterns = parse_patterns(hidden_patterns, "hidden");
+		self
+	}
+
+	/// Sets whether requests for paths with any component matching one of the
+	/// [`ListingFileServer::exclude_patterns`], or not matching any of the
+	/// [`ListingFileServer::include_patterns`], are forwarded, as if they
+	/// didn't exist.
+	///
+	/// This is disabled by default, so excluded entries are only hidden.
+	#[must_use]
+	pub fn deny_excluded(mut self, deny_excluded: bool) -> Self {
+		self.deny_excluded = deny_excluded;
+		self
+	}
+
+	/// Sets the maximum number of entries shown in a directory listing.
+	///
+	/// Listings with more entries are sorted, then truncated to the first
+	/// `max_listing_entries`, with [`Listing::truncated`] set. Use
+	/// [`ListingFileServer::listing_overflow_status`] to fail instead.
+	///
+	/// This is unlimited by default.
+	#[must_use]
+	pub fn max_listing_entries(mut self, max_listing_entries: usize) -> Self {
+		self.max_listing_entries = Some(max_listing_entries);
+		self
+	}
+
+	/// Sets the status that listings with more than the
+	/// [`ListingFileServer::max_listing_entries`] fail with, instead of being
+	/// truncated.
+	#[must_use]
+	pub fn listing_overflow_status(mut self, status: Status) -> Self {
+		self.listing_overflow_status = Some(status);
+		self
+	}
+
+	/// Sets the maximum number of segments a requested path may have, relative
+	/// to the server root.
+	///
+	/// Requests for deeper paths are forwarded before the filesystem is
+	/// touched, or fail with the [`ListingFileServer::path_depth_status`] if
+	/// set.
+	///
+	/// This is unlimited by default.
+	#[must_use]
+	pub fn max_path_depth(mut self, max_path_depth: usize) -> Self {
+		self.max_path_depth = Some(max_path_depth);
+		self
+	}
+
+	/// Sets the status that requests for paths deeper than the
+	/// [`ListingFileServer::max_path_depth`] fail with, such as
+	/// [`Status::NotFound`], instead of being forwarded.
+	#[must_use]
+	pub fn path_depth_status(mut self, status: Status) -> Self {
+		self.path_depth_status = Some(status);
+		self
+	}
+
+	/// Sets the name of a marker file, such as `.noindex`, that prevents the
+	/// directory containing it from being listed.
+	///
+	/// Requests for the listing of a marked directory are treated as if the
+	/// directory didn't exist, or fail with the
+	/// [`ListingFileServer::noindex_status`] if set. This includes archive
+	/// downloads, feeds, and searches of the directory. Files within it,
+	/// including its index file, are still served, and the marker itself is
+	/// left out of listings. Searches of parent directories aren't affected.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn noindex_marker<S: Into<String>>(mut self, noindex_marker: S) -> Self {
+		self.noindex_marker = Some(noindex_marker.into());
+		self
+	}
+
+	/// Sets the status that listings of directories with the
+	/// [`ListingFileServer::noindex_marker`] fail with, such as
+	/// [`Status::Forbidden`].
+	///
+	/// By default, they're treated as if the directory didn't exist.
+	#[must_use]
+	pub fn noindex_status(mut self, status: Status) -> Self {
+		self.noindex_status = Some(status);
+		self
+	}
+
+	/// Sets the status that listings fail with when the directory is removed
+	/// between being found and being read, such as [`Status::Gone`].
+	///
+	/// These requests aren't forwarded, since the path was a directory when it
+	/// was checked and other routes likely can't serve it either. Each one is
+	/// logged at the debug level.
+	///
+	/// By default, they're answered with the
+	/// [`ListingFileServer::not_found_template`] if one is configured, or fail
+	/// with [`Status::NotFound`] otherwise.
+	#[must_use]
+	pub fn vanished_status(mut self, status: Status) -> Self {
+		self.vanished_status = Some(status);
+		self
+	}
+
+	/// Sets the number of entries shown on each page of a directory listing,
+	/// splitting large listings into pages.
+	///
+	/// Clients can choose the page with the `page` query parameter, starting
+	/// from 1, and override the page size with the `per_page` query parameter.
+	/// A page size of 0 lists every entry on one page.
+	///
+	/// This is disabled by default, so listings are only paginated if the
+	/// client asks for a page size.
+	#[must_use]
+	pub fn page_size(mut self, page_size: usize) -> Self {
+		self.page_size = (page_size > 0).then_some(page_size);
+		self
+	}
+
+	/// Sets whether each directory's optional `.listing.toml` file is read to
+	/// configure how that directory is served.
+	///
+	/// The file may set an `index` filename, tried before the
+	/// [`ListingFileServer::index_files`] when [`rocket::fs::Options::Index`]
+	/// is enabled, and a `title` passed to the renderer as
+	/// [`Listing::title`]:
+	///
+	/// ```toml
+	/// index = "README.html"
+	/// title = "Project Documents"
+	/// ```
+	///
+	/// With overlaid roots, the first directory with a valid file is used. A
+	/// missing or malformed file is ignored, and the file itself is left out
+	/// of listings.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn directory_config(mut self, directory_config: bool) -> Self {
+		self.directory_config = directory_config;
+		self
+	}
+
+	/// Sets a filter deciding, per request, which paths may be accessed.
+	///
+	/// The filter is called with the request and a path relative to the server
+	/// root, such as `docs/report.pdf`, for every file or directory requested
+	/// and for every entry of a listing. Requests for paths it rejects are
+	/// forwarded, as if they didn't exist, unless
+	/// [`ListingFileServer::forbid_filtered`] is enabled, and rejected entries
+	/// are left out of listings.
+	///
+	/// Archives leave out the entries it rejects too, along with the contents
+	/// of rejected directories.
+	#[must_use]
+	pub fn access_filter<F>(mut self, access_filter: F) -> Self
+	where
+		F: Fn(&Request<'_>, &Path) -> bool + Send + Sync + 'static,
+	{
+		self.access_filter = Some(Arc::new(access_filter));
+		self
+	}
+
+	/// Sets a callback that observes every file, listing, and archive served,
+	/// such as for access logging or metrics.
+	///
+	/// The callback is called with the request and an [`AccessEvent`]
+	/// describing the response, just before it's returned. It can't change
+	/// the response. Requests that are forwarded or fail aren't reported.
+	#[must_use]
+	pub fn on_access<F>(mut self, on_access: F) -> Self
+	where
+		F: Fn(&Request<'_>, AccessEvent) + Send + Sync + 'static,
+	{
+		self.on_access = Some(Arc::new(on_access));
+		self
+	}
+
+	/// Sets whether directory listings are streamed to the client as they're
+	/// read, rather than only once the whole directory has been read.
+	///
+	/// This is meant for directories with tens of thousands of entries.
+	/// Streamed listings are always rendered with the built-in HTML page
+	/// rather than the server's renderer, and entries are listed in the order
+	/// they're read, so they aren't sorted or paginated. Listings that are too
+	/// large are truncated, whatever the
+	/// [`ListingFileServer::listing_overflow_status`]. Streamed listings have
+	/// no validators, so they're never cached.
+	///
+	/// Searches, feeds, and JSON listings are still collected in full, and
+	/// listings aren't streamed while an
+	/// [`ListingFileServer::access_filter`] is set, since the listing is
+	/// written without access to the request.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn stream_listings(mut self, stream_listings: bool) -> Self {
+		self.stream_listings = stream_listings;
+		self
+	}
+
+	/// Sets a renderer for the HTML table row of each entry of streamed
+	/// listings, in place of the built-in one.
+	///
+	/// This only has an effect when [`ListingFileServer::stream_listings`] is
+	/// enabled. The renderer's output is written as-is, so it must escape the
+	/// entry's name and href itself.
+	#[must_use]
+	pub fn stream_entry_renderer<F>(mut self, stream_entry_renderer: F) -> Self
+	where
+		F: Fn(&ListingEntry) -> String + Send + Sync + 'static,
+	{
+		self.stream_entry_renderer = Some(Arc::new(stream_entry_renderer));
+		self
+	}
+
+	/// Sets whether images can be served as thumbnails, using the `thumb` query
+	/// parameter to give the largest width and height in pixels (for example,
+	/// `?thumb=128`).
+	///
+	/// Thumbnails are generated on demand, and keep the aspect ratio of the
+	/// image. Sizes of up to 1024 pixels may be requested. Requests for files
+	/// that aren't images, or that can't be decoded, ignore the parameter and
+	/// serve the file as normal. Use
+	/// [`ListingFileServer::thumbnail_cache_dir`] to avoid generating the same
+	/// thumbnail more than once.
+	///
+	/// This is disabled by default.
+	#[cfg(feature = "image")]
+	#[must_use]
+	pub fn enable_thumbnails(mut self, enable_thumbnails: bool) -> Self {
+		self.enable_thumbnails = enable_thumbnails;
+		self
+	}
+
+	/// Sets the directory that generated thumbnails are cached in, such as a
+	/// directory within [`std::env::temp_dir`].
+	///
+	/// The directory is created if it doesn't exist. Cached thumbnails are
+	/// keyed by the modification time of their image, so stale thumbnails are
+	/// never served, but they're also never cleaned up.
+	///
+	/// The default is not to cache thumbnails.
+	#[cfg(feature = "image")]
+	#[must_use]
+	pub fn thumbnail_cache_dir<P: Into<PathBuf>>(mut self, thumbnail_cache_dir: P) -> Self {
+		self.thumbnail_cache_dir = Some(thumbnail_cache_dir.into());
+		self
+	}
+
+	/// Sets the cross-origin resource sharing (CORS) policy for responses, so
+	/// that listings and files can be fetched by front-ends on other origins.
+	///
+	/// This also registers an `OPTIONS` route alongside the `GET` and `HEAD`
+	/// routes, to answer preflight requests.
+	///
+	/// The default is no policy, so no CORS headers are sent.
+	#[must_use]
+	pub fn cors(mut self, cors: CorsConfig) -> Self {
+		self.cors = Some(cors);
+		self
+	}
+
+	/// Sets custom CSS that's added to the built-in listing page after its
+	/// default style, so that it can be themed without writing a renderer.
+	///
+	/// This is only used by the built-in HTML renderer of
+	/// [`ListingFileServer::from_path`] and by streamed listings, and is
+	/// ignored when a custom renderer is supplied. The default is no extra
+	/// CSS.
+	#[must_use]
+	pub fn inline_css<S: Into<String>>(mut self, inline_css: S) -> Self {
+		self.inline_css = Some(inline_css.into());
+		self
+	}
+
+	/// Sets a renderer for the page served with a [`Status::NotFound`] when a
+	/// requested file or directory doesn't exist, instead of forwarding the
+	/// request.
+	///
+	/// The renderer is called with the requested path, relative to the server
+	/// root. Requests that are forwarded for other reasons, such as those
+	/// rejected by the [`ListingFileServer::access_filter`], are unaffected,
+	/// and not-found pages aren't reported to the
+	/// [`ListingFileServer::on_access`] callback.
+	///
+	/// By default, such requests are forwarded so that other routes can handle
+	/// them.
+	#[must_use]
+	pub fn not_found_template<R, T>(mut self, not_found_renderer: R) -> Self
+	where
+		R: 'static + Fn(&Path) -> T + Send + Sync,
+		T: for<'r> Responder<'r, 'static>,
+	{
+		self.not_found_renderer = Some(Arc::new(move |req, path| {
+			not_found_renderer(path).respond_to(req)
+		}));
+		self
+	}
+
+	/// Sets a renderer for the page served when rendering a directory listing
+	/// fails, instead of failing the request with no context.
+	///
+	/// The renderer is called with the path of the directory, relative to the
+	/// server root, and the status the listing failed with, which the page is
+	/// served with. Two kinds of failure are caught:
+	///
+	/// - The listing's responder failing, such as a
+	///   `rocket_dyn_templates::Template` that refers to a missing template or
+	///   variable, or that can't be rendered because the template fairing isn't
+	///   attached, or a renderer from [`ListingFileServer::new_fallible`]
+	///   returning an error. The status is the one the listing failed with,
+	///   usually [`Status::InternalServerError`].
+	/// - The template-rendering function panicking, which is served with
+	///   [`Status::InternalServerError`]. This relies on unwinding, so it
+	///   doesn't apply if the application is built with `panic = "abort"`.
+	///
+	/// Failures while streaming a listing, or in JSON listings and feeds, which
+	/// don't use the template, aren't caught. If the error page itself fails,
+	/// the request fails with the original status.
+	///
+	/// By default, the request fails with the status the listing failed with.
+	#[must_use]
+	pub fn error_template<R, T>(mut self, error_renderer: R) -> Self
+	where
+		R: 'static + Fn(&Path, Status) -> T + Send + Sync,
+		T: for<'r> Responder<'r, 'static>,
+	{
+		self.error_renderer = Some(Arc::new(move |req, path, status| {
+			error_renderer(path, status).respond_to(req)
+		}));
+		self
+	}
+
+	/// Sets a handler that requests are passed to, with the same request and
+	/// data, instead of being forwarded.
+	///
+	/// This applies to every request that would otherwise be forwarded, such
+	/// as those for missing paths and those rejected by the
+	/// [`ListingFileServer::access_filter`], so the fallback sees the same
+	/// requests that lower-ranked routes would. The fallback may itself
+	/// forward the request, such as another [`ListingFileServer`] serving a
+	/// mirror. A [`ListingFileServer::not_found_template`] is still used for
+	/// missing paths instead, if set.
+	///
+	/// By default, such requests are forwarded so that other routes can handle
+	/// them.
+	#[must_use]
+	pub fn fallback<H: Handler>(mut self, fallback: H) -> Self {
+		self.fallback = Some(Arc::new(fallback));
+		self
+	}
+
+	/// Sets whether requests rejected by the
+	/// [`ListingFileServer::access_filter`] fail with `403 Forbidden` instead
+	/// of being forwarded.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn forbid_filtered(mut self, forbid_filtered: bool) -> Self {
+		self.forbid_filtered = forbid_filtered;
+		self
+	}
+
+	/// Sets whether directory listings can be searched with the `q` query
+	/// parameter.
+	///
+	/// A search walks the subtree beneath the requested directory, and lists
+	/// every entry with a name matching the query in place of the directory's
+	/// own entries. Queries containing `*`, `?`, or `[` are matched as glob
+	/// patterns, and any other query matches names containing it, ignoring
+	/// case in both. Each result's name and href are its path relative to the
+	/// requested directory, and the query is passed to the renderer as
+	/// [`Listing::search`].
+	///
+	/// The cost of a search is bounded by
+	/// [`ListingFileServer::search_limits`].
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn enable_search(mut self, enable_search: bool) -> Self {
+		self.enable_search = enable_search;
+		self
+	}
+
+	/// Sets the maximum number of directory levels a search descends beneath
+	/// the requested directory, and the maximum number of results it finds.
+	///
+	/// A `max_depth` of 0 only searches the requested directory itself.
+	///
+	/// The defaults are a depth of 8 and 500 results.
+	#[must_use]
+	pub fn search_limits(mut self, max_depth: usize, max_results: usize) -> Self {
+		self.search_max_depth = max_depth;
+		self.search_max_results = max_results;
+		self
+	}
+
+	/// Sets whether the most recently modified files beneath a directory can
+	/// be listed with the `recent` query parameter, such as `?recent=50`.
+	///
+	/// This walks the subtree beneath the requested directory, and lists the
+	/// given number of files with the newest modification times, newest first,
+	/// in place of the directory's own entries. Each file's name and href are
+	/// its path relative to the requested directory, and the number requested
+	/// is passed to the renderer as [`Listing::recent`]. A search takes
+	/// precedence if both are requested.
+	///
+	/// The cost of walking the subtree is bounded by
+	/// [`ListingFileServer::recent_limits`].
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn enable_recent(mut self, enable_recent: bool) -> Self {
+		self.enable_recent = enable_recent;
+		self
+	}
+
+	/// Sets the maximum number of directory levels walked beneath the
+	/// requested directory for its most recently modified files, and the
+	/// maximum number of files considered.
+	///
+	/// Once `max_entries` files have been found, the rest of the subtree is
+	/// left out. A `max_depth` of 0 only considers the requested directory
+	/// itself.
+	///
+	/// The defaults are a depth of 8 and 10,000 files.
+	#[must_use]
+	pub fn recent_limits(mut self, max_depth: usize, max_entries: usize) -> Self {
+		self.recent_max_depth = max_depth;
+		self.recent_max_entries = max_entries;
+		self
+	}
+
+	/// Sets the `Cache-Control` policy for files served, including index
+	/// files.
+	///
+	/// This is disabled by default, so no `Cache-Control` header is sent.
+	#[must_use]
+	pub fn file_cache_control(mut self, cache_control: CacheControl) -> Self {
+		self.file_cache_control = Some(cache_control);
+		self
+	}
+
+	/// Sets the `Cache-Control` policy for directory listings.
+	///
+	/// Listings change whenever the directory does, so
+	/// [`CacheControl::NoCache`] is usually the best fit, combined with
+	/// [`ListingFileServer::cache_listings`] for cheap revalidation.
+	///
+	/// This is disabled by default, so no `Cache-Control` header is sent.
+	#[must_use]
+	pub fn listing_cache_control(mut self, cache_control: CacheControl) -> Self {
+		self.listing_cache_control = Some(cache_control);
+		self
+	}
+
+	/// Sets whether directories can be served as an Atom feed of their files
+	/// with the `feed=atom` or `format=atom` query parameters, or to clients
+	/// whose `Accept` header prefers `application/atom+xml`.
+	///
+	/// Each file in the listing becomes a feed entry linking to it, timestamped
+	/// with its modified time and sorted newest first regardless of the
+	/// listing's sort. The feed isn't paginated. Its links are absolute, built
+	/// with the [`ListingFileServer::public_origin`] if set.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn enable_feed(mut self, enable_feed: bool) -> Self {
+		self.enable_feed = enable_feed;
+		self
+	}
+
+	/// Sets whether directories can be downloaded as an M3U playlist of their
+	/// media files with the `format=m3u` query parameter, or by clients whose
+	/// `Accept` header prefers `audio/x-mpegurl`.
+	///
+	/// The playlist links to each file with one of the
+	/// [`ListingFileServer::playlist_extensions`], in the listing's sort order,
+	/// so it can be opened directly by media players. It isn't paginated. Its
+	/// links are absolute, built with the [`ListingFileServer::public_origin`]
+	/// if set.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn enable_playlist(mut self, enable_playlist: bool) -> Self {
+		self.enable_playlist = enable_playlist;
+		self
+	}
+
+	/// Sets the extensions of the files included in playlists, such as `mp3`
+	/// or `webm`.
+	///
+	/// Extensions are matched ignoring case, and may be given with or without
+	/// their leading `.`. The default is a set of common audio and video
+	/// formats.
+	#[must_use]
+	pub fn playlist_extensions<S: AsRef<str>>(mut self, extensions: &[S]) -> Self {
+		self.playlist_extensions = extensions
+			.iter()
+			.map(|extension| {
+				let extension = extension.as_ref();
+				extension
+					.strip_prefix('.')
+					.unwrap_or(extension)
+					.to_lowercase()
+			})
+			.collect();
+		self
+	}
+
+	/// Sets which entries are shown in directory listings, by whether they're
+	/// directories.
+	///
+	/// Entries left out by the filter can still be requested directly, and
+	/// the `../` entry is unaffected.
+	///
+	/// The default is [`EntryFilter::All`].
+	#[must_use]
+	pub fn entry_filter(mut self, entry_filter: EntryFilter) -> Self {
+		self.entry_filter = entry_filter;
+		self
+	}
+
+	/// Sets the URL path that the server root is reached at by clients,
+	/// overriding the path the server is mounted at.
+	///
+	/// This is used to build [`Listing::url`] and [`Listing::breadcrumbs`],
+	/// and is useful when a reverse proxy serves the routes under a different
+	/// path, such as `/downloads` for routes mounted at `/`.
+	///
+	/// By default, the path the server is mounted at is used.
+	#[must_use]
+	pub fn url_prefix<S: Into<String>>(mut self, url_prefix: S) -> Self {
+		self.url_prefix = Some(url_prefix.into());
+		self
+	}
+
+	/// Sets the scheme and host that clients reach the server at, such as
+	/// `https://example.com`, for building the absolute links of Atom feeds
+	/// and M3U playlists.
+	///
+	/// Any path belongs in [`ListingFileServer::url_prefix`] instead, since
+	/// the links are built by appending [`Listing::url`] to the origin. A
+	/// trailing `/` is ignored.
+	///
+	/// By default, the origin is taken from the request's `Host` header,
+	/// which clients can set to anything, so the links in a feed or playlist
+	/// can point wherever they like. This should be set when those formats are
+	/// served behind a reverse proxy or cached.
+	#[must_use]
+	pub fn public_origin<S: Into<String>>(mut self, public_origin: S) -> Self {
+		let public_origin = public_origin.into();
+		self.public_origin = Some(public_origin.trim_end_matches('/').to_owned());
+		self
+	}
+
+	/// Sets whether precompressed siblings of files are served in their place
+	/// to clients that accept their encoding.
+	///
+	/// When a file such as `app.js` is requested, `app.js.br` is served with
+	/// `Content-Encoding: br` if it exists and the client accepts Brotli, and
+	/// otherwise `app.js.gz` is served with `Content-Encoding: gzip` if it
+	/// exists and the client accepts gzip. Either way, the response has the
+	/// content type of the original file. If neither applies, the original
+	/// file is served as normal. This also applies to index files. Files are
+	/// sent with `Vary: Accept-Encoding` while this is enabled.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn precompressed(mut self, precompressed: bool) -> Self {
+		self.precompressed = precompressed;
+		self
+	}
+
+	/// Sets the content types that served files are given by their extension,
+	/// taking precedence over the type guessed from the extension.
+	///
+	/// Extensions are matched ignoring case, and may be given with or without
+	/// their leading `.`, such as `wasm` or `.webmanifest`. Files with other
+	/// extensions keep the guessed type. The default is no overrides.
+	#[must_use]
+	pub fn content_type_overrides(
+		mut self,
+		content_type_overrides: HashMap<String, ContentType>,
+	) -> Self {
+		self.content_type_overrides = content_type_overrides
+			.into_iter()
+			.map(|(extension, content_type)| {
+				let extension = extension.strip_prefix('.').unwrap_or(&extension);
+				(extension.to_lowercase(), content_type)
+			})
+			.collect();
+		self
+	}
+
+	/// Sets the extensions of files that clients are told to download rather
+	/// than display, such as `csv` or `svg`.
+	///
+	/// Matching files are served with a `Content-Disposition: attachment`
+	/// header naming the file. Extensions are matched ignoring case, and may
+	/// be given with or without their leading `.`. Other files are left to be
+	/// displayed inline. The default is no extensions.
+	#[must_use]
+	pub fn force_download_extensions<S: AsRef<str>>(mut self, extensions: &[S]) -> Self {
+		self.force_download_extensions = extensions
+			.iter()
+			.map(|extension| {
+				let extension = extension.as_ref();
+				extension
+					.strip_prefix('.')
+					.unwrap_or(extension)
+					.to_lowercase()
+			})
+			.collect();
+		self
+	}
+
+	/// Sets the largest file, in bytes, that's served.
+	///
+	/// Larger files, including index files, fail with the
+	/// [`ListingFileServer::oversized_status`] instead. Sizes are checked with
+	/// the file's metadata before it's opened, so nothing is read. Archives
+	/// leave larger files out. Directory listings are unaffected, and still
+	/// show larger files.
+	///
+	/// This is unlimited by default.
+	#[must_use]
+	pub fn max_file_size(mut self, max_file_size: u64) -> Self {
+		self.max_file_size = Some(max_file_size);
+		self
+	}
+
+	/// Sets the status that requests for files larger than the
+	/// [`ListingFileServer::max_file_size`] fail with, such as
+	/// [`Status::Forbidden`].
+	///
+	/// The default is [`Status::PayloadTooLarge`].
+	#[must_use]
+	pub fn oversized_status(mut self, status: Status) -> Self {
+		self.oversized_status = Some(status);
+		self
+	}
+
+	/// Sets whether directory listings and textual files are compressed with
+	/// gzip for clients that accept it.
+	///
+	/// Only textual content types, such as HTML, JSON, and plain text, are
+	/// compressed, since images, archives, and other binary formats usually
+	/// are already. Compressed responses are streamed, so they don't carry a
+	/// `Content-Length`. Responses that may be compressed are sent with
+	/// `Vary: Accept-Encoding`, whether they were or not.
+	///
+	/// This is disabled by default.
+	#[must_use]
+	pub fn compression(mut self, compression: bool) -> Self {
+		self.compression = compression;
+		self
+	}
+
+	/// Sets the candidate README filenames looked for, in order, in each
+	/// listed directory.
+	///
+	/// The contents of the first candidate that exists are passed to the
+	/// renderer as [`Listing::readme`], to be shown alongside the entries. The
+	/// file is still listed as normal. Files larger than 1 MiB, or that aren't
+	/// valid UTF-8, are skipped.
+	///
+	/// The default is empty, so no README is read.
+	#[must_use]
+	pub fn readme_files<S: AsRef<str>>(mut self, readme_files: &[S]) -> Self {
+		self.readme_files = readme_files
+			.iter()
+			.map(|file| file.as_ref().to_owned())
+			.collect();
+		self
+	}
+
+	/// Reads the entries of the directory at `path`, relative to the server
+	/// root, lazily and in the order they're read.
+	///
+	/// The entries are filtered the same way as in listings, by the dotfile,
+	/// exclude pattern, and entry filter settings and so on, but the access
+	/// filter isn't applied, since there's no request to check. The entries
+	/// are named as they are on disk, without any
+	/// [`ListingFileServer::dir_suffix`]. They can be sorted with
+	/// [`ListingIterator::sorted`].
+	///
+	/// If several roots have the directory, their entries are read in order,
+	/// leaving out names already read from an earlier root. Unlike listings,
+	/// an entry is only treated as a directory if it's one in the root it was
+	/// first read from.
+	///
+	/// The directory is read synchronously, so this shouldn't be called from
+	/// an asynchronous context without moving it to a blocking thread.
+	///
+	/// # Errors
+	///
+	/// Returns an error of kind [`io::ErrorKind::InvalidInput`] if `path`
+	/// isn't a plain relative path, of kind [`io::ErrorKind::NotFound`] if no
+	/// root has a directory at `path`, or the error encountered while opening
+	/// the directory otherwise.
+	pub fn iter_listing<P: AsRef<Path>>(&self, path: P) -> io::Result<ListingIterator> {
+		let path = path.as_ref();
+		if !is_plain_relative(path) {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"the path must be relative to the server root",
+			));
+		}
+		let mut readers = VecDeque::new();
+		for candidate in self.entry_reader().candidates(path) {
+			if fs::metadata(&candidate).is_ok_and(|m| m.is_dir()) {
+				readers.push_back(fs::read_dir(candidate)?);
+			}
+		}
+		if readers.is_empty() {
+			return Err(io::Error::new(
+				io::ErrorKind::NotFound,
+				"no root has a directory at the path",
+			));
+		}
+		Ok(ListingIterator {
+			server: self.clone(),
+			reader: self.entry_reader(),
+			readers,
+			path: path.to_owned(),
+			seen: HashSet::new(),
+		})
+	}
+
+	/// Checks that the server is able to serve, without mounting it.
+	///
+	/// Every root is opened for reading. If `req` is given, the renderer is
+	/// also called with the listing of an empty root directory, so that a
+	/// renderer that fails or panics is caught early. In tests, `req` can be
+	/// any [`rocket::local`] request, which dereferences to a [`Request`].
+	/// Nothing is written, and no files are read beyond opening each root.
+	///
+	/// # Errors
+	///
+	/// Returns the error encountered while opening a root, or an error of kind
+	/// [`io::ErrorKind::Other`] if the renderer fails or panics.
+	pub fn validate(&self, req: Option<&Request<'_>>) -> io::Result<()> {
+		use std::panic::{catch_unwind, AssertUnwindSafe};
+
+		for root in &self.roots {
+			fs::read_dir(root)?;
+		}
+		let Some(req) = req else {
+			return Ok(());
+		};
+		let breadcrumbs = breadcrumbs(&self.url_base(req), "/");
+		let listing = Listing {
+			directory: String::from('/'),
+			url: breadcrumbs[0].1.clone(),
+			request_path: req.uri().path().to_string(),
+			mount_base: mount_base(req).to_owned(),
+			link_query: link_query(req),
+			breadcrumbs,
+			segments: Vec::new(),
+			entries: Vec::new(),
+			sort_by: self.sort_by,
+			sort_order: self.sort_order,
+			pagination: None,
+			groups: self.group_by_extension.then(Vec::new),
+			title: None,
+			search: None,
+			recent: None,
+			readme: None,
+			truncated: false,
+			empty: true,
+			summary: ListingSummary::default(),
+		};
+		match catch_unwind(AssertUnwindSafe(|| self.render(req, listing))) {
+			Ok(Ok(_)) => Ok(()),
+			Ok(Err(status)) => Err(io::Error::other(format!(
+				"the listing renderer failed with {status}"
+			))),
+			Err(_) => Err(io::Error::other("the listing renderer panicked")),
+		}
+	}
+}
+
+impl ListingFileServer {
+	/// Checks whether the entry name `name` matches any of the exclude
+	/// patterns.
+	fn is_excluded(&self, name: &str) -> bool {
+		self.exclude_patterns
+			.iter()
+			.any(|pattern| pattern.matches(name))
+	}
+
+	/// Checks whether the entry name `name`, of a directory if `is_dir` is
+	/// set, is allowed by the include patterns.
+	fn is_included(&self, name: &str, is_dir: bool) -> bool {
+		is_included(
+			name,
+			is_dir,
+			&self.include_patterns,
+			self.always_include_dirs,
+		)
+	}
+
+	/// Builds the reader that entries are read with, from the server's
+	/// settings.
+	fn entry_reader(&self) -> EntryReader {
+		let reserved_names = self
+			.directory_config
+			.then(|| String::from(DIRECTORY_CONFIG_FILE))
+			.into_iter()
+			.chain(self.noindex_marker.clone())
+			.collect();
+		EntryReader {
+			roots: self.roots.clone(),
+			hide_dotfiles: self.hide_dotfiles_in_listing,
+			skip_non_utf8_names: self.skip_non_utf8_names,
+			exclude_patterns: self.exclude_patterns.clone(),
+			include_patterns: self.include_patterns.clone(),
+			always_include_dirs: self.always_include_dirs,
+			hidden_patterns: self.hidden_patterns.clone(),
+			reserved_names,
+			entry_filter: self.entry_filter,
+			follow_symlinks: self.follow_symlinks,
+			follow_file_symlinks: self.follow_file_symlinks,
+			follow_dir_symlinks: self.follow_dir_symlinks,
+			show_symlink_targets: self.show_symlink_targets,
+			size_units: self.size_units,
+		}
+	}
+
+	/// Replaces the display name of `entry`, found in the directory at `path`,
+	/// with its label from the display name function, if it has one.
+	fn label_entry(&self, path: &Path, entry: &mut ListingEntry) {
+		if let Some(display_name_fn) = &self.display_name_fn {
+			if let Some(label) = display_name_fn(&path.join(&entry.name)) {
+				entry.display_name = label;
+			}
+		}
+	}
+
+	/// Checks whether the access filter, if any, allows `req` to access `path`,
+	/// relative to the server root.
+	fn is_accessible(&self, req: &Request<'_>, path: &Path) -> bool {
+		self.access_filter
+			.as_ref()
+			.is_none_or(|access_filter| access_filter(req, path))
+	}
+
+	/// Opens the precompressed sibling of the file at `file_path` in the best
+	/// encoding that `req` accepts, if enabled and one exists.
+	///
+	/// Returns the sibling along with the name of its content coding.
+	async fn open_precompressed(
+		&self,
+		req: &Request<'_>,
+		file_path: &Path,
+	) -> Option<(NamedFile, &'static str)> {
+		if !self.precompressed {
+			return None;
+		}
+		let encodings = [
+			("br", "br", accepts_brotli(req)),
+			("gz", "gzip", accepts_gzip(req)),
+		];
+		for (extension, encoding, _) in encodings.into_iter().filter(|(.., accepted)| *accepted) {
+			let mut sibling = file_path.as_os_str().to_owned();
+			sibling.push(".");
+			sibling.push(extension);
+			let sibling = PathBuf::from(sibling);
+			if !metadata(&sibling).await.is_ok_and(|m| m.is_file())
+				|| !self.resolves_within_roots(&sibling).await
+				|| (!self.follow_file_symlinks
+					&& symlink_metadata(&sibling)
+						.await
+						.is_ok_and(|m| m.is_symlink()))
+			{
+				continue;
+			}
+			if let Ok(file) = NamedFile::open(&sibling).await {
+				return Some((file, encoding));
+			}
+		}
+		None
+	}
+
+	/// Checks whether the file at `path` is larger than the maximum file size,
+	/// if one is configured.
+	async fn is_oversized(&self, path: &Path) -> bool {
+		let Some(max_file_size) = self.max_file_size else {
+			return false;
+		};
+		metadata(path)
+			.await
+			.is_ok_and(|metadata| metadata.len() > max_file_size)
+	}
+
+	/// Responds to `req` with `file`, found at `path`, applying the file cache
+	/// policy and compression.
+	///
+	/// If `encoding` is set, `file` is a precompressed sibling of the file at
+	/// `path` with that content coding, and is served as such.
+	async fn file_outcome<'r>(
+		&self,
+		req: &'r Request<'_>,
+		path: &Path,
+		file: NamedFile,
+		encoding: Option<&'static str>,
+	) -> Outcome<'r> {
+		let metadata = file.metadata().await.ok();
+		let len = metadata.as_ref().map_or(0, Metadata::len);
+		let modified = metadata.and_then(|metadata| metadata.modified().ok());
+		// Check whether the client's cached copy is still fresh
+		if let Some(modified) = modified.filter(|&modified| is_file_fresh(req, modified)) {
+			let mut response = Response::build()
+				.status(Status::NotModified)
+				.header(last_modified_header(modified))
+				.finalize();
+			if let Some(cache_control) = self.file_cache_control {
+				cache_control.apply(&mut response);
+			}
+			return self.served(req, path, AccessKind::File, response).await;
+		}
+		let range = RequestedRange::from_request(req, len, modified);
+		let range_file = match range {
+			RequestedRange::Partial(..) => file.try_clone().await.ok(),
+			_ => None,
+		};
+		let extension = path
+			.extension()
+			.map(|extension| extension.to_string_lossy().to_lowercase());
+		let disposition = extension
+			.as_ref()
+			.filter(|extension| self.force_download_extensions.contains(extension))
+			.and(path.file_name())
+			.map(|name| attachment_disposition(&name.to_string_lossy()));
+		let content_type = extension.and_then(|extension| {
+			self.content_type_overrides
+				.get(&extension)
+				.cloned()
+				.or_else(|| encoding.and_then(|_| ContentType::from_extension(&extension)))
+		});
+		match file.respond_to(req) {
+			Ok(mut response) => {
+				if let Some(modified) = modified {
+					response.set_header(last_modified_header(modified));
+				}
+				if let Some(encoding) = encoding {
+					// The type guessed is that of the sibling, such as gzip
+					response.remove_header("Content-Type");
+					response.set_header(Header::new("Content-Encoding", encoding));
+				}
+				if let Some(content_type) = content_type {
+					response.set_header(content_type);
+				}
+				if let Some(disposition) = disposition {
+					response.set_header(Header::new("Content-Disposition", disposition));
+				}
+				range.apply(&mut response, range_file, len).await;
+				if let Some(cache_control) = self.file_cache_control {
+					cache_control.apply(&mut response);
+				}
+				if self.compression && accepts_gzip(req) {
+					compress(req, &mut response).await;
+				}
+				self.served(req, path, AccessKind::File, response).await
+			}
+			Err(status) => Outcome::failure(status),
+		}
+	}
+
+	/// Succeeds with `response`, a `kind` of response for `path`, after
+	/// setting its `Vary` and CORS headers and reporting it to the access
+	/// callback, if configured.
+	async fn served<'r>(
+		&self,
+		req: &'r Request<'_>,
+		path: &Path,
+		kind: AccessKind,
+		mut response: Response<'r>,
+	) -> Outcome<'r> {
+		self.apply_vary(&mut response, kind);
+		if let Some(cors) = &self.cors {
+			cors.apply(req, &mut response);
+		}
+		if let Some(on_access) = &self.on_access {
+			let size = response.body_mut().size().await;
+			on_access(
+				req,
+				AccessEvent {
+					path: path.to_owned(),
+					kind,
+					status: response.status(),
+					size: size.map(|size| size as u64),
+					client_ip: req.client_ip(),
+				},
+			);
+		}
+		Outcome::Success(response)
+	}
+
+	/// Adds the request headers that a `kind` of response was negotiated on to
+	/// its `Vary` header, so that shared caches don't serve it to clients that
+	/// would've been sent something else.
+	///
+	/// This includes responses that weren't compressed, or were sent in the
+	/// default format, since those depend on the headers as much as the rest.
+	fn apply_vary(&self, response: &mut Response<'_>, kind: AccessKind) {
+		let varies_on_encoding = match kind {
+			AccessKind::Listing => {
+				if self.enabled_formats().len() > 1 {
+					adjoin_vary(response, "Accept");
+				}
+				self.compression
+			}
+			AccessKind::File => {
+				self.precompressed
+					|| (self.compression
+						&& response
+							.content_type()
+							.is_some_and(|content_type| is_compressible(&content_type)))
+			}
+			AccessKind::Archive => false,
+		};
+		if varies_on_encoding {
+			adjoin_vary(response, "Accept-Encoding");
+		}
+	}
+
+	/// Responds with a thumbnail of the image at `candidate`, found at `path`,
+	/// if thumbnails are enabled and one was requested.
+	///
+	/// Returns [`None`] if the file should be served as normal instead.
+	#[cfg(feature = "image")]
+	async fn thumbnail_outcome<'r>(
+		&self,
+		req: &'r Request<'_>,
+		path: &Path,
+		candidate: &Path,
+	) -> Option<Outcome<'r>> {
+		if !self.enable_thumbnails {
+			return None;
+		}
+		let size = req
+			.query_value::<u32>("thumb")
+			.and_then(Result::ok)
+			.filter(|size| (1..=MAX_THUMBNAIL_SIZE).contains(size))?;
+		let name = candidate.file_name()?.to_str()?;
+		if guess_type(name, false).1 != EntryKind::Image {
+			return None;
+		}
+		let thumbnail = thumbnail(
+			candidate.to_path_buf(),
+			size,
+			self.thumbnail_cache_dir.clone(),
+		)
+		.await?;
+		let outcome = match (thumbnail.content_type, thumbnail.data).respond_to(req) {
+			Ok(mut response) => {
+				if let Some(cache_control) = self.file_cache_control {
+					cache_control.apply(&mut response);
+				}
+				self.served(req, path, AccessKind::File, response).await
+			}
+			Err(status) => Outcome::failure(status),
+		};
+		Some(outcome)
+	}
+
+	/// Passes `req` on to the fallback handler, if one is configured, or
+	/// forwards it otherwise.
+	async fn forward<'r>(&self, req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r> {
+		match &self.fallback {
+			Some(fallback) => fallback.handle(req, data).await,
+			None => Outcome::forward(data),
+		}
+	}
+
+	/// Responds to a request for the missing path `path` with the not-found
+	/// page, if one is configured, or forwards it otherwise.
+	async fn not_found<'r>(
+		&self,
+		req: &'r Request<'_>,
+		path: &Path,
+		data: Data<'r>,
+	) -> Outcome<'r> {
+		let Some(not_found_renderer) = &self.not_found_renderer else {
+			return self.forward(req, data).await;
+		};
+		match not_found_renderer(req, path) {
+			Ok(mut response) => {
+				response.set_status(Status::NotFound);
+				if let Some(cors) = &self.cors {
+					cors.apply(req, &mut response);
+				}
+				Outcome::Success(response)
+			}
+			Err(status) => Outcome::failure(status),
+		}
+	}
+
+	/// Checks whether any of `directories` has the noindex marker, if one is
+	/// configured.
+	async fn is_noindexed(&self, directories: &[PathBuf]) -> bool {
+		let Some(noindex_marker) = &self.noindex_marker else {
+			return false;
+		};
+		for directory in directories {
+			if symlink_metadata(directory.join(noindex_marker))
+				.await
+				.is_ok()
+			{
+				return true;
+			}
+		}
+		false
+	}
+
+	/// Responds to a request for the listing of `path`, a directory with the
+	/// noindex marker.
+	async fn noindexed<'r>(
+		&self,
+		req: &'r Request<'_>,
+		path: &Path,
+		data: Data<'r>,
+	) -> Outcome<'r> {
+		match self.noindex_status {
+			Some(status) => Outcome::failure(status),
+			None => self.not_found(req, path, data).await,
+		}
+	}
+
+	/// Responds to a request for the listing of `path`, a directory that was
+	/// removed before it could be read.
+	async fn vanished<'r>(&self, req: &'r Request<'_>, path: &Path, data: Data<'r>) -> Outcome<'r> {
+		debug_!(
+			"Directory `{}` was removed before it could be listed.",
+			path.display()
+		);
+		match self.vanished_status {
+			Some(status) => Outcome::failure(status),
+			None if self.not_found_renderer.is_some() => self.not_found(req, path, data).await,
+			None => Outcome::failure(Status::NotFound),
+		}
+	}
+
+	/// Reads the first README file found in `directories`, found at `req_path`.
+	async fn read_readme(
+		&self,
+		req: &Request<'_>,
+		req_path: &Path,
+		directories: &[PathBuf],
+	) -> Option<String> {
+		for directory in directories {
+			for readme_file in &self.readme_files {
+				if !self.is_accessible(req, &req_path.join(readme_file)) {
+					continue;
+				}
+				let path = directory.join(readme_file);
+				if !metadata(&path)
+					.await
+					.is_ok_and(|m| m.is_file() && m.len() <= Self::MAX_README_SIZE)
+				{
+					continue;
+				}
+				if let Ok(readme) = read_to_string(path).await {
+					return Some(readme);
+				}
+			}
+		}
+		None
+	}
+
+	/// Resolves `path`, relative to the server root, against each root in
+	/// order, as [`EntryReader::candidates`] does.
+	///
+	/// Checking the paths calls into the filesystem, so that's done on a
+	/// blocking thread.
+	async fn candidates(&self, path: &Path) -> Vec<PathBuf> {
+		let entry_reader = self.entry_reader();
+		let path = path.to_owned();
+		spawn_blocking(move || entry_reader.candidates(&path))
+			.await
+			.unwrap_or_default()
+	}
+
+	/// Checks whether `path`, beneath one of the roots, resolves within it, as
+	/// [`EntryReader::resolves_within`] does, on a blocking thread.
+	async fn resolves_within_roots(&self, path: &Path) -> bool {
+		let entry_reader = self.entry_reader();
+		let path = path.to_owned();
+		spawn_blocking(move || entry_reader.resolves_within_roots(&path))
+			.await
+			.unwrap_or(false)
+	}
+
+	/// Checks whether every symlink along `path`, relative to `base`, is of a
+	/// kind that's followed, as [`EntryReader::follows_symlinks_within`] does.
+	async fn follows_symlinks_within(&self, base: &Path, path: &Path) -> bool {
+		if self.follow_file_symlinks && self.follow_dir_symlinks {
+			return true;
+		}
+		let entry_reader = self.entry_reader();
+		let (base, path) = (base.to_owned(), path.to_owned());
+		spawn_blocking(move || entry_reader.follows_symlinks_within(&base, &path))
+			.await
+			.unwrap_or(false)
+	}
+
+	/// Searches the subtree of `directories`, found at `req_path`, for entries
+	/// with names matching `query`.
+	///
+	/// The subtree is walked descending at most the configured maximum depth,
+	/// until the configured maximum number of results is found. The name and
+	/// href of each result are relative to `req_path`.
+	async fn search(
+		&self,
+		req: &Request<'_>,
+		req_path: &Path,
+		directories: Vec<PathBuf>,
+		query: &SearchQuery,
+	) -> Vec<ListingEntry> {
+		let mut results = Vec::new();
+		self.walk(req, req_path, directories, self.search_max_depth, |entry| {
+			if query.matches(&entry.name) {
+				results.push(entry);
+			}
+			results.len() < self.search_max_results
+		})
+		.await;
+		results
+	}
+
+	/// Collects the files in the subtree of `directories`, found at
+	/// `req_path`, for listing the most recently modified of them.
+	///
+	/// The subtree is walked descending at most the configured maximum depth,
+	/// until the configured maximum number of files is found. The name and
+	/// href of each file are relative to `req_path`.
+	async fn recent_files(
+		&self,
+		req: &Request<'_>,
+		req_path: &Path,
+		directories: Vec<PathBuf>,
+	) -> Vec<ListingEntry> {
+		let mut files = Vec::new();
+		self.walk(req, req_path, directories, self.recent_max_depth, |entry| {
+			if !entry.is_dir {
+				files.push(entry);
+			}
+			files.len() < self.recent_max_entries
+		})
+		.await;
+		files
+	}
+
+	/// Walks the subtree of `directories`, found at `req_path`, breadth-first,
+	/// passing each entry to `visit` until it returns `false`.
+	///
+	/// At most `max_depth` directory levels are descended. The name and href of
+	/// each entry are made relative to `req_path`, with the entries of each
+	/// directory in the order they're read.
+	async fn walk<F>(
+		&self,
+		req: &Request<'_>,
+		req_path: &Path,
+		directories: Vec<PathBuf>,
+		max_depth: usize,
+		mut visit: F,
+	) where
+		F: FnMut(ListingEntry) -> bool + Send,
+	{
+		let mut pending =
+			VecDeque::from([(PathBuf::new(), String::new(), String::new(), directories, 0)]);
+		while let Some((relative, name_prefix, href_prefix, directories, depth)) =
+			pending.pop_front()
+		{
+			let Ok(entries) = self
+				.read_entries(req, &req_path.join(&relative), &directories)
+				.await
+			else {
+				continue;
+			};
+			for mut entry in entries {
+				if entry.is_dir && depth < max_depth {
+					let child = relative.join(&entry.name);
+					let child_directories =
+						existing_directories(&self.candidates(&req_path.join(&child)).await).await;
+					pending.push_back((
+						child,
+						format!("{name_prefix}{}/", entry.name),
+						format!("{href_prefix}{}", entry.href),
+						child_directories,
+						depth + 1,
+					));
+				}
+				entry.name.insert_str(0, &name_prefix);
+				entry.href.insert_str(0, &href_prefix);
+				if !visit(entry) {
+					return;
+				}
+			}
+		}
+	}
+
+	/// Walks the subtree of `directories`, found at `req_path`, with
+	/// `entry_reader` as [`EntryReader::walk_tree`] does, on a blocking thread.
+	///
+	/// Entries that `req` may not access are left out, along with the contents
+	/// of directories it may not access.
+	async fn visible_tree(
+		&self,
+		req: &Request<'_>,
+		req_path: &Path,
+		directories: Vec<PathBuf>,
+		max_depth: usize,
+		entry_reader: EntryReader,
+	) -> io::Result<Vec<TreeEntry>> {
+		let tree = spawn_blocking(move || entry_reader.walk_tree(&directories, max_depth))
+			.await
+			.map_err(io::Error::other)??;
+		Ok(prune_tree(tree, |tree_entry| {
+			self.is_accessible(req, &req_path.join(&tree_entry.relative))
+		}))
+	}
+
+	/// Replaces each directory of `entries`, found at `req_path`, that contains
+	/// only a single subdirectory with the deepest directory of the chain.
+	///
+	/// The collapsed entries are named and linked relative to `req_path`.
+	async fn collapse_dir_chains(
+		&self,
+		req: &Request<'_>,
+		req_path: &Path,
+		entries: &mut [ListingEntry],
+	) {
+		for entry in entries.iter_mut().filter(|entry| entry.is_dir) {
+			let mut chain = PathBuf::from(&entry.name);
+			for _ in 0..Self::MAX_COLLAPSED_DEPTH {
+				let path = req_path.join(&chain);
+				let directories = existing_directories(&self.candidates(&path).await).await;
+				let Ok(mut children) = self.read_entries(req, &path, &directories).await else {
+					break;
+				};
+				let child = match children.pop() {
+					Some(child) if children.is_empty() && child.is_dir => child,
+					_ => break,
+				};
+				chain.push(&child.name);
+				let name = format!("{}/{}", entry.name, child.name);
+				let href = format!("{}{}", entry.href, child.href);
+				*entry = ListingEntry {
+					display_name: name.clone(),
+					name,
+					href,
+					..child
+				};
+			}
+		}
+	}
+
+	/// Renders `listing` for `req` with the custom renderer, or the built-in
+	/// HTML renderer if there's none.
+	fn render(&self, req: &Request<'_>, listing: Listing) -> response::Result<'static> {
+		match &self.renderer {
+			Some(renderer) => renderer(req, listing),
+			None => Html(render_listing(&listing, self.inline_css.as_deref())).respond_to(req),
+		}
+	}
+
+	/// Renders `listing`, of the directory at `path`, for `req` as
+	/// [`ListingFileServer::render`] does, but serves the error page instead
+	/// if rendering fails or panics and an error page is configured.
+	fn render_or_error(
+		&self,
+		req: &Request<'_>,
+		path: &Path,
+		listing: Listing,
+	) -> response::Result<'static> {
+		use std::panic::{catch_unwind, AssertUnwindSafe};
+
+		let Some(error_renderer) = &self.error_renderer else {
+			return self.render(req, listing);
+		};
+		let status = match catch_unwind(AssertUnwindSafe(|| self.render(req, listing))) {
+			Ok(Ok(response)) => return Ok(response),
+			Ok(Err(status)) => status,
+			Err(_) => Status::InternalServerError,
+		};
+		let mut response = error_renderer(req, path, status).map_err(|_| status)?;
+		response.set_status(status);
+		Ok(response)
+	}
+
+	/// The listing formats that are enabled, HTML first.
+	fn enabled_formats(&self) -> Vec<ListingFormat> {
+		[
+			(ListingFormat::Html, true),
+			(ListingFormat::Json, self.enable_json),
+			(ListingFormat::Text, self.enable_text),
+			(ListingFormat::Atom, self.enable_feed),
+			(ListingFormat::M3u, self.enable_playlist),
+		]
+		.into_iter()
+		.filter_map(|(format, enabled)| enabled.then_some(format))
+		.collect()
+	}
+
+	/// Gets the format to serve the listing in for `req`, of those enabled.
+	///
+	/// A format requested with the `format` or `feed` query parameters is used
+	/// first, then the format the `Accept` header prefers, then the default
+	/// format.
+	fn listing_format(&self, req: &Request<'_>) -> ListingFormat {
+		let formats = self.enabled_formats();
+		let requested = req
+			.query_value::<ListingFormat>("format")
+			.and_then(Result::ok)
+			.or_else(|| {
+				req.query_value::<FeedFormat>("feed")
+					.and_then(Result::ok)
+					.map(|FeedFormat::Atom| ListingFormat::Atom)
+			});
+		if let Some(format) = requested.filter(|format| formats.contains(format)) {
+			return format;
+		}
+		let default = Some(self.default_listing_format)
+			.filter(|format| formats.contains(format))
+			.unwrap_or_default();
+		negotiate(req.accept(), &formats, default)
+	}
+
+	/// Gets the URL path that listing links are built from, as seen by
+	/// clients.
+	fn url_base(&self, req: &Request<'_>) -> String {
+		if let Some(url_prefix) = &self.url_prefix {
+			return url_prefix.clone();
+		}
+		format!(
+			"{}{}",
+			mount_base(req).trim_end_matches('/'),
+			self.route_base
+		)
+	}
+
+	/// Gets the scheme and host that absolute links in responses to `req`
+	/// start with, as described for [`ListingFileServer::public_origin`].
+	fn origin(&self, req: &Request<'_>) -> String {
+		self.public_origin
+			.clone()
+			.unwrap_or_else(|| request_origin(req))
+	}
+
+	/// Gets the URL of the directory at `directory` for `req`, as
+	/// [`Listing::url`], along with its breadcrumbs.
+	///
+	/// With [`TrailingSlash::StripFromAll`], the crumbs apart from the root
+	/// link without a trailing slash, the same as the directories are served.
+	/// The URL always has one, so that links can be resolved against it.
+	fn breadcrumbs(&self, req: &Request<'_>, directory: &str) -> (String, Vec<(String, String)>) {
+		let mut crumbs = breadcrumbs(&self.url_base(req), directory);
+		let url = crumbs
+			.last()
+			.map(|(_, href)| href.clone())
+			.unwrap_or_default();
+		if self.trailing_slash.strips_from_dirs() {
+			for (_, href) in crumbs.iter_mut().skip(1) {
+				href.pop();
+			}
+		}
+		(url, crumbs)
+	}
+
+	/// Builds a response that streams the HTML listing of the directories
+	/// being read by `readers`, with the rest of the page built from
+	/// `listing`.
+	///
+	/// The entries are written on a blocking thread as the response body is
+	/// read. Nothing is written for a `HEAD` request `req`, whose body is
+	/// dropped anyway.
+	fn stream_listing(
+		&self,
+		req: &Request<'_>,
+		listing: Listing,
+		readers: Vec<fs::ReadDir>,
+	) -> Response<'static> {
+		let (writer, reader) = duplex(Self::STREAM_BUFFER_SIZE);
+		if req.method() != Method::Head {
+			let mut writer = BlockingWriter::new(writer);
+			let server = self.clone();
+			spawn_blocking(move || {
+				if let Err(e) = server.write_streamed_listing(&mut writer, listing, readers) {
+					warn_!("Streamed listing was cut short: {}", e);
+				}
+			});
+		}
+		Response::build()
+			.header(ContentType::HTML)
+			.streamed_body(reader)
+			.finalize()
+	}
+
+	/// Writes the HTML listing of the directories being read by `readers` to
+	/// `writer`, an entry at a time.
+	///
+	/// Entries are written in the order they're read, so they aren't sorted.
+	/// Where several directories have an entry with the same name, the first
+	/// one read wins.
+	fn write_streamed_listing<W: Write>(
+		&self,
+		writer: &mut W,
+		mut listing: Listing,
+		readers: Vec<fs::ReadDir>,
+	) -> io::Result<()> {
+		let render_entry = |entry: &ListingEntry| match &self.stream_entry_renderer {
+			Some(stream_entry_renderer) => stream_entry_renderer(entry),
+			None => render_row(entry),
+		};
+		let breadcrumbs = listing.breadcrumbs.clone();
+		let name_entry = |mut entry: ListingEntry| {
+			if let Some(suffix) = self.dir_suffix.filter(|_| entry.is_dir) {
+				entry.name.push(suffix);
+				entry.display_name.push(suffix);
+			}
+			if self.trailing_slash.strips_from_dirs() {
+				strip_trailing_slash(&mut entry, &breadcrumbs);
+			}
+			entry
+		};
+		let entry_reader = self.entry_reader();
+		let path = PathBuf::from(listing.directory.trim_start_matches('/'));
+
+		writer.write_all(render_head(&listing, false, self.inline_css.as_deref()).as_bytes())?;
+		if self.show_parent_link && listing.directory != "/" {
+			let parent = name_entry(parent_entry());
+			writer.write_all(render_entry(&parent).as_bytes())?;
+		}
+		let mut names = HashSet::new();
+		'directories: for entries in readers {
+			for entry in entries.filter_map(Result::ok) {
+				let Some(merged) = entry_reader.read_entry(&entry) else {
+					continue;
+				};
+				let mut listing_entry = merged.entry;
+				if names.contains(&listing_entry.name) || !entry_reader.shows(&listing_entry) {
+					continue;
+				}
+				if self
+					.max_listing_entries
+					.is_some_and(|max| names.len() >= max)
+				{
+					listing.truncated = true;
+					break 'directories;
+				}
+				names.insert(listing_entry.name.clone());
+				self.label_entry(&path, &mut listing_entry);
+				let listing_entry = name_entry(listing_entry);
+				writer.write_all(render_entry(&listing_entry).as_bytes())?;
+			}
+		}
+		listing.empty = names.is_empty();
+		writer.write_all(render_tail(&listing).as_bytes())
+	}
+
+	/// Reads the entries of `directories`, merging them by name, as
+	/// [`list_directory`] does but with the server's settings.
+	///
+	/// Where several directories have an entry with the same name, the first
+	/// one wins, except that the entry is treated as a directory if it's a
+	/// directory in any of them. Entries that `req` may not access at
+	/// `req_path` are left out.
+	///
+	/// The directories are read on a blocking thread, so a slow filesystem
+	/// stalls only the request being served rather than a whole worker
+	/// thread. Each entry still costs up to one `stat` call, so listing very
+	/// large directories takes proportionally longer.
+	async fn read_entries(
+		&self,
+		req: &Request<'_>,
+		req_path: &Path,
+		directories: &[PathBuf],
+	) -> io::Result<Vec<ListingEntry>> {
+		let entry_reader = self.entry_reader();
+		let directories = directories.to_vec();
+		let mut entry_list = spawn_blocking(move || entry_reader.read_entries(&directories))
+			.await
+			.map_err(io::Error::other)??;
+		entry_list.retain(|entry| self.is_accessible(req, &req_path.join(&entry.name)));
+		Ok(entry_list)
+	}
+}
+
+/// An iterator over the entries of a directory served by a
+/// [`ListingFileServer`], read lazily as it's advanced.
+///
+/// This is created by [`ListingFileServer::iter_listing`].
+#[derive(Debug)]
+pub struct ListingIterator {
+	/// The server whose settings the entries are filtered by.
+	server: ListingFileServer,
+	/// The reader the entries are read with.
+	reader: EntryReader,
+	/// The readers of the directory in each root that has it, in order.
+	readers: VecDeque<fs::ReadDir>,
+	/// The path of the directory, relative to the server root.
+	path: PathBuf,
+	/// The names of the entries yielded so far.
+	seen: HashSet<String>,
+}
+
+impl ListingIterator {
+	/// Collects the rest of the entries, sorted by the field `by` in the order
+	/// `order`, using the server's other sort settings.
+	#[must_use]
+	pub fn sorted(self, by: SortBy, order: SortOrder) -> Vec<ListingEntry> {
+		let sort_options = self.server.sort_options.clone();
+		let mut entries = self.collect::<Vec<_>>();
+		sort_entries(&mut entries, by, order, &sort_options);
+		entries
+	}
+}
+
+impl Iterator for ListingIterator {
+	type Item = ListingEntry;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while let Some(reader) = self.readers.front_mut() {
+			let Some(entry) = reader.next() else {
+				self.readers.pop_front();
+				continue;
+			};
+			let Ok(entry) = entry else {
+				continue;
+			};
+			let Some(merged) = self.reader.read_entry(&entry) else {
+				continue;
+			};
+			let mut listing_entry = merged.entry;
+			if self.seen.contains(&listing_entry.name) || !self.reader.shows(&listing_entry) {
+				continue;
+			}
+			self.seen.insert(listing_entry.name.clone());
+			self.server.label_entry(&self.path, &mut listing_entry);
+			return Some(listing_entry);
+		}
+		None
+	}
+}
+
+/// Reads the entries of the directory at `path`, without a server or a
+/// request.
+///
+/// Dotfiles are left out unless `options` contains [`Options::DotFiles`], and
+/// the entries are sorted by the field `sort_by` in the order `sort_order`,
+/// with directories first. Symlinks are always followed.
+///
+/// Servers read their directories the same way, but with their own settings,
+/// such as their exclude patterns, applied too. This leaves those at their
+/// defaults and reads the directory synchronously, which makes it suited to
+/// tools and tests that only need the entries.
+///
+/// # Errors
+///
+/// Returns an error if the directory can't be read. Entries that can't be
+/// read are skipped, and entries whose metadata can't be read are listed
+/// without it.
+pub fn list_directory<P: AsRef<Path>>(
+	path: P,
+	options: Options,
+	sort_by: SortBy,
+	sort_order: SortOrder,
+) -> io::Result<Vec<ListingEntry>> {
+	let path = path.as_ref().to_owned();
+	let mut entry_reader = EntryReader::new(vec![path.clone()]);
+	entry_reader.hide_dotfiles = !options.contains(Options::DotFiles);
+	let mut entry_list = entry_reader.read_entries(&[path])?;
+	sort_entries(
+		&mut entry_list,
+		sort_by,
+		sort_order,
+		&SortOptions::default(),
+	);
+	Ok(entry_list)
+}
+
+/// Filters `candidates` down to those that are directories.
+async fn existing_directories(candidates: &[PathBuf]) -> Vec<PathBuf> {
+	let mut directories = Vec::new();
+	for candidate in candidates {
+		if metadata(candidate).await.is_ok_and(|m| m.is_dir()) {
+			directories.push(candidate.clone());
+		}
+	}
+	directories
+}
+
+/// Gets the latest modified time of `directories`, if any can be read.
+async fn latest_modified(directories: &[PathBuf]) -> Option<SystemTime> {
+	let mut latest = None;
+	for directory in directories {
+		if let Ok(modified) = metadata(directory).await.and_then(|m| m.modified()) {
+			latest = latest.max(Some(modified));
+		}
+	}
+	latest
+}
+
+/// Strips the trailing slash from the link of `entry`, of a listing with the
+/// breadcrumbs `breadcrumbs`, if it's a directory.
+///
+/// The `../` entry is linked to the parent directory's breadcrumb instead,
+/// since `..` resolves to a URL with a trailing slash.
+fn strip_trailing_slash(entry: &mut ListingEntry, breadcrumbs: &[(String, String)]) {
+	if entry.href == "../" {
+		if let Some((_, parent)) = breadcrumbs.len().checked_sub(2).map(|i| &breadcrumbs[i]) {
+			entry.href.clone_from(parent);
+		}
+	} else if entry.is_dir {
+		let len = entry.href.trim_end_matches('/').len();
+		entry.href.truncate(len);
+	}
+}
+
+/// Builds the `../` entry linking to the parent directory.
+fn parent_entry() -> ListingEntry {
+	ListingEntry {
+		name: String::from(".."),
+		display_name: String::from(".."),
+		href: String::from("../"),
+		is_dir: true,
+		size: None,
+		human_size: None,
+		modified: None,
+		content_type: None,
+		kind: EntryKind::Directory,
+		is_symlink: false,
+		symlink_target: None,
+		is_hidden: false,
+		mode: None,
+		is_readable: true,
+	}
+}
+
+/// Gets the scheme and host that `req` was sent to, such as
+/// `https://example.com`, from its `Host` header, or an empty string if it has
+/// none.
+fn request_origin(req: &Request<'_>) -> String {
+	let scheme = if req.rocket().config().tls_enabled() {
+		"https"
+	} else {
+		"http"
+	};
+	req.headers()
+		.get_one("Host")
+		.map(|host| format!("{scheme}://{host}"))
+		.unwrap_or_default()
+}
+
+/// Gets the parameters of the query of `req` that links re-sorting or paging
+/// through a listing keep, as described for [`Listing::link_query`].
+fn link_query(req: &Request<'_>) -> String {
+	let Some(query) = req.uri().query() else {
+		return String::new();
+	};
+	query
+		.raw_segments()
+		.map(RawStr::as_str)
+		.filter(|segment| {
+			let name = segment.split('=').next().unwrap_or_default();
+			LINK_QUERY_PARAMETERS.contains(&name)
+		})
+		.collect::<Vec<_>>()
+		.join("&")
+}
+
+/// Builds the path of the last `count` segments of the path of `req` from
+/// their raw bytes, so that names that aren't valid UTF-8 are kept intact.
+///
+/// The segments must already have been checked by
+/// [`Segments::to_path_buf`], which this follows in skipping empty segments
+/// and resolving `..`.
+#[cfg(unix)]
+fn raw_request_path(req: &Request<'_>, count: usize) -> PathBuf {
+	use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+	use percent_encoding::percent_decode;
+
+	let raw_segments = req
+		.uri()
+		.path()
+		.raw_segments()
+		.filter(|segment| !segment.is_empty())
+		.collect::<Vec<_>>();
+	let mut path = PathBuf::new();
+	for segment in &raw_segments[raw_segments.len().saturating_sub(count)..] {
+		let name = percent_decode(segment.as_bytes()).collect::<Vec<_>>();
+		if name == b".." {
+			path.pop();
+		} else {
+			path.push(OsStr::from_bytes(&name));
+		}
+	}
+	path
+}
+
+/// Gets the path the server is mounted at, as matched by `req`.
+fn mount_base<'r>(req: &'r Request<'_>) -> &'r str {
+	req.route().map_or("/", |route| route.uri.base())
+}
+
+/// Builds the path of the directory at `req_path`, relative to the server
+/// root, beginning and ending with a `/`.
+///
+/// Names that aren't valid UTF-8 are converted lossily, rather than failing
+/// the request.
+fn directory_path(req_path: &Path) -> String {
+	let mut directory = String::from('/');
+	// Join the components rather than replacing separators, since on Unix a
+	// `\` is an ordinary character in file names
+	for component in req_path.components() {
+		directory.push_str(&component.as_os_str().to_string_lossy());
+		directory.push('/');
+	}
+	directory
+}
+
+/// Parses `patterns` as glob patterns, used as the server's `purpose`
+/// patterns.
+///
+/// # Panics
+///
+/// Panics if any of the patterns is not a valid glob pattern.
+#[track_caller]
+fn parse_patterns<S: AsRef<str>>(patterns: &[S], purpose: &str) -> Vec<Pattern> {
+	use rocket::yansi::Paint;
+
+	patterns
+		.iter()
+		.map(|pattern| {
+			let pattern = pattern.as_ref();
+			Pattern::new(pattern).unwrap_or_else(|e| {
+				error!(
+					"ListingFileServer {} pattern '{}' is invalid: {}",
+					purpose,
+					Paint::white(pattern),
+					e
+				);
+				warn_!("Aborting early to prevent inevitable handler failure.");
+				panic!("bad ListingFileServer {purpose} pattern: refusing to continue");
+			})
+		})
+		.collect()
+}
+
+impl fmt::Debug for ListingFileServer {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut debug = f.debug_struct("ListingFileServer");
+		debug
+			.field("roots", &self.roots)
+			.field("root_handle", &self.root_handle)
+			.field("options", &self.options)
+			.field("rank", &self.rank)
+			.field("route_base", &self.route_base)
+			.field("sort_by", &self.sort_by)
+			.field("sort_order", &self.sort_order)
+			.field("sort_options", &self.sort_options)
+			.field("comparator", &self.comparator.is_some())
+			.field("group_by_extension", &self.group_by_extension)
+			.field("index_files", &self.index_files)
+			.field("index_at_root_only", &self.index_at_root_only)
+			.field("show_parent_link", &self.show_parent_link)
+			.field("follow_symlinks", &self.follow_symlinks)
+			.field("follow_file_symlinks", &self.follow_file_symlinks)
+			.field("follow_dir_symlinks", &self.follow_dir_symlinks)
+			.field("skip_non_utf8_names", &self.skip_non_utf8_names)
+			.field("enable_json", &self.enable_json)
+			.field("enable_text", &self.enable_text)
+			.field("default_listing_format", &self.default_listing_format)
+			.field("enable_archive_download", &self.enable_archive_download)
+			.field("cache_listings", &self.cache_listings)
+			.field(
+				"render_cache",
+				&self.render_cache.as_ref().map(|cache| cache.capacity()),
+			)
+			.field("hide_dotfiles_in_listing", &self.hide_dotfiles_in_listing)
+			.field("exclude_patterns", &self.exclude_patterns)
+			.field("include_patterns", &self.include_patterns)
+			.field("always_include_dirs", &self.always_include_dirs)
+			.field("hidden_patterns", &self.hidden_patterns)
+			.field("deny_excluded", &self.deny_excluded)
+			.field("page_size", &self.page_size)
+			.field("directory_config", &self.directory_config)
+			.field("access_filter", &self.access_filter.is_some())
+			.field("forbid_filtered", &self.forbid_filtered)
+			.field("enable_search", &self.enable_search)
+			.field("search_max_depth", &self.search_max_depth)
+			.field("search_max_results", &self.search_max_results)
+			.field("enable_recent", &self.enable_recent)
+			.field("recent_max_depth", &self.recent_max_depth)
+			.field("recent_max_entries", &self.recent_max_entries)
+			.field("file_cache_control", &self.file_cache_control)
+			.field("listing_cache_control", &self.listing_cache_control)
+			.field("enable_feed", &self.enable_feed)
+			.field("enable_playlist", &self.enable_playlist)
+			.field("playlist_extensions", &self.playlist_extensions)
+			.field("readme_files", &self.readme_files)
+			.field("entry_filter", &self.entry_filter)
+			.field("url_prefix", &self.url_prefix)
+			.field("public_origin", &self.public_origin)
+			.field("inline_css", &self.inline_css)
+			.field("show_symlink_targets", &self.show_symlink_targets)
+			.field("compression", &self.compression)
+			.field("precompressed", &self.precompressed)
+			.field("content_type_overrides", &self.content_type_overrides)
+			.field("force_download_extensions", &self.force_download_extensions)
+			.field("max_file_size", &self.max_file_size)
+			.field("oversized_status", &self.oversized_status)
+			.field("dotfile_denied_status", &self.dotfile_denied_status)
+			.field("on_access", &self.on_access.is_some())
+			.field("max_listing_entries", &self.max_listing_entries)
+			.field("listing_overflow_status", &self.listing_overflow_status)
+			.field("noindex_marker", &self.noindex_marker)
+			.field("max_path_depth", &self.max_path_depth)
+			.field("path_depth_status", &self.path_depth_status)
+			.field("noindex_status", &self.noindex_status)
+			.field("vanished_status", &self.vanished_status)
+			.field("dir_suffix", &self.dir_suffix)
+			.field("display_name_fn", &self.display_name_fn.is_some())
+			.field("size_units", &self.size_units)
+			.field("not_found_renderer", &self.not_found_renderer.is_some())
+			.field("error_renderer", &self.error_renderer.is_some())
+			.field("fallback", &self.fallback.is_some())
+			.field("trailing_slash", &self.trailing_slash)
+			.field(
+				"normalize_redirect_permanent",
+				&self.normalize_redirect_permanent,
+			)
+			.field("stream_listings", &self.stream_listings)
+			.field(
+				"stream_entry_renderer",
+				&self.stream_entry_renderer.is_some(),
+			)
+			.field("collapse_single_dirs", &self.collapse_single_dirs)
+			.field("compute_dir_sizes", &self.compute_dir_sizes)
+			.field("dir_size_max_depth", &self.dir_size_max_depth)
+			.field("cors", &self.cors);
+		#[cfg(feature = "image")]
+		debug
+			.field("enable_thumbnails", &self.enable_thumbnails)
+			.field("thumbnail_cache_dir", &self.thumbnail_cache_dir);
+		debug.finish_non_exhaustive()
+	}
+}
+
+impl From<ListingFileServer> for Vec<Route> {
+	fn from(server: ListingFileServer) -> Self {
+		let source = figment::Source::File(server.roots[0].clone());
+		// `HEAD` is routed explicitly, rather than left to Rocket's fallback to
+		// `GET`, so that streamed bodies that Rocket strips aren't written at all
+		let mut methods = vec![Method::Get, Method::Head];
+		if server.cors.is_some() {
+			methods.push(Method::Options);
+		}
+		methods
+			.into_iter()
+			.map(|method| {
+				let path = format!("{}/<path..>", server.route_base);
+				let mut route = Route::ranked(server.rank, method, &path, server.clone());
+				route.name = Some(format!("ListingFileServer: {source}/").into());
+				route
+			})
+			.collect()
+	}
+}
+
+#[async_trait]
+impl Handler for ListingFileServer {
+	async fn handle<'r>(&self, req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r> {
+		use rocket::http::uri::fmt::Path;
+
+		// Answer CORS preflight requests, which are only routed if configured
+		if req.method() == Method::Options {
+			return match &self.cors {
+				Some(cors) => Outcome::Success(cors.preflight(req)),
+				None => self.forward(req, data).await,
+			};
+		}
+
+		// Get the segments as a `PathBuf`, allowing dotfiles requested.
+		let options = self.options;
+		let allow_dotfiles = options.contains(Options::DotFiles);
+		let route_base_len = self.route_base.matches('/').count();
+		let segments = req.segments::<Segments<'_, Path>>(route_base_len..).ok();
+		let req_path = segments
+			.clone()
+			.and_then(|segments| segments.to_path_buf(allow_dotfiles).ok());
+		let Some(req_path) = req_path else {
+			// Fail with the configured status if it was only refused for a dotfile
+			if let Some(status) = self.dotfile_denied_status {
+				if !allow_dotfiles
+					&& segments.is_some_and(|segments| segments.to_path_buf(true).is_ok())
+				{
+					return Outcome::failure(status);
+				}
+			}
+			return self.forward(req, data).await;
+		};
+		// Rocket decodes names lossily, so recover those that aren't UTF-8
+		#[cfg(unix)]
+		let req_path = match &segments {
+			Some(segments) if req_path.to_string_lossy().contains('\u{fffd}') => {
+				raw_request_path(req, segments.len())
+			}
+			_ => req_path,
+		};
+		if !is_plain_relative(&req_path) {
+			return self.forward(req, data).await;
+		}
+		if self
+			.max_path_depth
+			.is_some_and(|max_path_depth| req_path.components().count() > max_path_depth)
+		{
+			return match self.path_depth_status {
+				Some(status) => Outcome::failure(status),
+				None => self.forward(req, data).await,
+			};
+		}
+		if self.deny_excluded
+			&& req_path
+				.components()
+				.any(|component| self.is_excluded(&component.as_os_str().to_string_lossy()))
+		{
+			return self.forward(req, data).await;
+		}
+		if !self.is_accessible(req, &req_path) {
+			return if self.forbid_filtered {
+				Outcome::failure(Status::Forbidden)
+			} else {
+				self.forward(req, data).await
+			};
+		}
+
+		// Resolve the path against each root, in order
+		let candidates = self.candidates(&req_path).await;
+		let directories = existing_directories(&candidates).await;
+		if self.deny_excluded && !self.include_patterns.is_empty() {
+			// Every component but a requested file is a directory
+			let depth = req_path.components().count();
+			let denied = req_path.components().enumerate().any(|(i, component)| {
+				let is_dir = i + 1 < depth || !directories.is_empty();
+				!self.is_included(&component.as_os_str().to_string_lossy(), is_dir)
+			});
+			if denied {
+				return self.forward(req, data).await;
+			}
+		}
+
+		// File
+		if directories.is_empty() {
+			for candidate in candidates {
+				if self.is_oversized(&candidate).await {
+					return Outcome::failure(
+						self.oversized_status.unwrap_or(Status::PayloadTooLarge),
+					);
+				}
+				if let Ok(file) = NamedFile::open(&candidate).await {
+					// Normalize '/a/b/foo.txt/?q' to '/a/b/foo.txt?q', if configured
+					if self.trailing_slash.strips_from_files() && req.uri().path().ends_with('/') {
+						let normal = req
+							.uri()
+							.map_path(|p| p.as_str().trim_end_matches('/').to_owned())
+							.expect(
+								"removing trailing slashes from a known good path => valid path",
+							)
+							.into_owned();
+						let redirect = if self.normalize_redirect_permanent {
+							Redirect::moved(normal)
+						} else {
+							Redirect::temporary(normal)
+						};
+						return Outcome::from_or_forward(req, data, redirect);
+					}
+					// Serve a thumbnail instead, if requested
+					#[cfg(feature = "image")]
+					if let Some(outcome) = self.thumbnail_outcome(req, &req_path, &candidate).await
+					{
+						return outcome;
+					}
+					// Serve a precompressed sibling instead, if accepted
+					let (file, encoding) = match self.open_precompressed(req, &candidate).await {
+						Some((sibling, encoding)) => (sibling, Some(encoding)),
+						None => (file, None),
+					};
+					return self.file_outcome(req, &req_path, file, encoding).await;
+				}
+			}
+			return self.not_found(req, &req_path, data).await;
+		}
+
+		// Directory
+		// Normalize '/a/b/foo/?q' to '/a/b/foo?q', if configured
+		let strips_from_dirs = self.trailing_slash.strips_from_dirs();
+		if strips_from_dirs && req.uri().path().ends_with('/') && !req_path.as_os_str().is_empty() {
+			let normal = req
+				.uri()
+				.map_path(|p| p.as_str().trim_end_matches('/').to_owned())
+				.expect("removing trailing slashes from a known good path => valid path")
+				.into_owned();
+			let redirect = if self.normalize_redirect_permanent {
+				Redirect::moved(normal)
+			} else {
+				Redirect::temporary(normal)
+			};
+			return Outcome::from_or_forward(req, data, redirect);
+		}
+		// Normalize '/a/b/foo?q' to '/a/b/foo/?q'. `map_path` keeps the query as-is.
+		if !strips_from_dirs
+			&& (options.contains(Options::NormalizeDirs) || self.trailing_slash.adds_to_dirs())
+			&& !req.uri().path().ends_with('/')
+		{
+			let normal = req
+				.uri()
+				.map_path(|p| format!("{p}/"))
+				.expect("adding a trailing slash to a known good path => valid path")
+				.into_owned();
+			// `NormalizeDirs` alone redirects like `FileServer` does
+			let redirect = if !self.normalize_redirect_permanent {
+				Redirect::temporary(normal)
+			} else if self.trailing_slash.adds_to_dirs() {
+				Redirect::moved(normal)
+			} else {
+				Redirect::permanent(normal)
+			};
+
+			return Outcome::from_or_forward(req, data, redirect);
+		}
+
+		// Refuse to list the directory if it's marked, still serving its index file
+		let noindexed = self.is_noindexed(&directories).await;
+
+		// Download the directory as an archive, if requested
+		if self.enable_archive_download {
+			if let Some(Ok(format)) = req.query_value::<ArchiveFormat>("download") {
+				if noindexed {
+					return self.noindexed(req, &req_path, data).await;
+				}
+				// A root opened from a handle is only named by its file descriptor
+				let directory = match &self.root_handle {
+					Some(_) => tokio::fs::canonicalize(&directories[0])
+						.await
+						.unwrap_or_else(|_| directories[0].clone()),
+					None => directories[0].clone(),
+				};
+				let name = directory.file_name().map_or_else(
+					|| String::from("archive"),
+					|name| name.to_string_lossy().into_owned(),
+				);
+				// Dotfiles that can't be downloaded on their own aren't bundled either
+				let mut entry_reader = self.entry_reader();
+				entry_reader.hide_dotfiles |= !allow_dotfiles;
+				// The archive isn't planned for `HEAD` requests, since it's never sent
+				let tree = if req.method() == Method::Head {
+					Ok(None)
+				} else {
+					self.visible_tree(
+						req,
+						&req_path,
+						directories.clone(),
+						usize::MAX,
+						entry_reader,
+					)
+					.await
+					.map(Some)
+				};
+				let tree = match tree {
+					// Files too large to download on their own aren't bundled either
+					Ok(tree) => tree.map(|tree| {
+						prune_tree(tree, |tree_entry| {
+							let entry = &tree_entry.merged.entry;
+							entry.is_dir
+								|| self
+									.max_file_size
+									.is_none_or(|max| entry.size.is_none_or(|size| size <= max))
+						})
+					}),
+					Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+						return Outcome::failure(Status::Forbidden);
+					}
+					// Removed since it was found
+					Err(e) if e.kind() == io::ErrorKind::NotFound => {
+						return self.vanished(req, &req_path, data).await;
+					}
+					Err(_) => return Outcome::failure(Status::InternalServerError),
+				};
+				let response = archive_response(format, tree, &name);
+				return self
+					.served(req, &req_path, AccessKind::Archive, response)
+					.await;
+			}
+		}
+
+		// Read the directory's own configuration, if enabled
+		let directory_config = if self.directory_config {
+			DirectoryConfig::read(&directories).await
+		} else {
+			DirectoryConfig::default()
+		};
+
+		// Search the subtree instead of listing, if requested
+		let search = if self.enable_search {
+			req.query_value::<String>("q")
+				.and_then(Result::ok)
+				.and_then(|q| Some((SearchQuery::parse(&q)?, q)))
+		} else {
+			None
+		};
+		// List the subtree's newest files instead, if requested
+		let recent = req
+			.query_value::<usize>("recent")
+			.filter(|_| self.enable_recent && search.is_none())
+			.and_then(Result::ok)
+			.filter(|&recent| recent > 0);
+		let walking = search.is_some() || recent.is_some();
+
+		if options.contains(Options::Index)
+			&& !walking
+			&& (!self.index_at_root_only || req_path.as_os_str().is_empty())
+		{
+			for directory in &directories {
+				for index_file in directory_config.index.iter().chain(&self.index_files) {
+					if !self.is_accessible(req, &req_path.join(index_file))
+						|| !self
+							.follows_symlinks_within(directory, std::path::Path::new(index_file))
+							.await
+					{
+						continue;
+					}
+					let index_path = directory.join(index_file);
+					if self.is_oversized(&index_path).await {
+						return Outcome::failure(
+							self.oversized_status.unwrap_or(Status::PayloadTooLarge),
+						);
+					}
+					if let Ok(index) = NamedFile::open(&index_path).await {
+						let (index, encoding) =
+							match self.open_precompressed(req, &index_path).await {
+								Some((sibling, encoding)) => (sibling, Some(encoding)),
+								None => (index, None),
+							};
+						return self
+							.file_outcome(req, &req_path.join(index_file), index, encoding)
+							.await;
+					}
+				}
+			}
+		}
+
+		if noindexed {
+			return self.noindexed(req, &req_path, data).await;
+		}
+
+		// Stream the listing as it's read, if configured and nothing else was requested
+		if self.stream_listings
+			&& self.access_filter.is_none()
+			&& !walking
+			&& self.listing_format(req) == ListingFormat::Html
+		{
+			let opened = directories.clone();
+			let readers = spawn_blocking(move || {
+				opened
+					.iter()
+					.map(fs::read_dir)
+					.collect::<io::Result<Vec<_>>>()
+			})
+			.await
+			.map_err(io::Error::other)
+			.and_then(|readers| readers);
+			let readers = match readers {
+				Ok(readers) => readers,
+				Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+					return Outcome::failure(Status::Forbidden);
+				}
+				// Removed since it was found
+				Err(e) if e.kind() == io::ErrorKind::NotFound => {
+					return self.vanished(req, &req_path, data).await;
+				}
+				Err(_) => return Outcome::failure(Status::InternalServerError),
+			};
+			let readme = self.read_readme(req, &req_path, &directories).await;
+			let directory = directory_path(&req_path);
+			let (url, breadcrumbs) = self.breadcrumbs(req, &directory);
+			let listing = Listing {
+				url,
+				request_path: req.uri().path().to_string(),
+				mount_base: mount_base(req).to_owned(),
+				link_query: link_query(req),
+				breadcrumbs,
+				segments: directory_segments(&directory),
+				directory,
+				entries: Vec::new(),
+				sort_by: self.sort_by,
+				sort_order: self.sort_order,
+				pagination: None,
+				groups: None,
+				title: directory_config.title,
+				search: None,
+				recent: None,
+				readme,
+				truncated: false,
+				empty: false,
+				summary: ListingSummary::default(),
+			};
+			let mut response = self.stream_listing(req, listing, readers);
+			if let Some(cache_control) = self.listing_cache_control {
+				cache_control.apply(&mut response);
+			}
+			if self.compression && accepts_gzip(req) {
+				compress(req, &mut response).await;
+			}
+			return self
+				.served(req, &req_path, AccessKind::Listing, response)
+				.await;
+		}
+
+		let walk_results = match &search {
+			Some((query, _)) => Some(
+				self.search(req, &req_path, directories.clone(), query)
+					.await,
+			),
+			None if recent.is_some() => {
+				Some(self.recent_files(req, &req_path, directories.clone()).await)
+			}
+			None => None,
+		};
+		let entry_list = match walk_results {
+			Some(results) => Ok(results),
+			None => self.read_entries(req, &req_path, &directories).await,
+		};
+		let mut entry_list = match entry_list {
+			Ok(entry_list) => entry_list,
+			// Unreadable directory
+			Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+				return Outcome::failure(Status::Forbidden);
+			}
+			// Removed since it was found
+			Err(e) if e.kind() == io::ErrorKind::NotFound => {
+				return self.vanished(req, &req_path, data).await;
+			}
+			Err(_) => return Outcome::failure(Status::InternalServerError),
+		};
+
+		// Keep only directories or files, and included entries, if configured
+		let entry_reader = self.entry_reader();
+		entry_list.retain(|entry| entry_reader.shows(entry));
+
+		// Collapse chains of single subdirectories, if configured
+		if self.collapse_single_dirs && !walking {
+			self.collapse_dir_chains(req, &req_path, &mut entry_list)
+				.await;
+		}
+
+		// Sum the sizes of subdirectories, if configured
+		if self.compute_dir_sizes && !walking {
+			for entry in entry_list
+				.iter_mut()
+				.filter(|entry| entry.is_dir && !entry.is_symlink)
+			{
+				let entry_path = req_path.join(&entry.name);
+				let directories = existing_directories(&self.candidates(&entry_path).await).await;
+				// Symlinks are never followed, so nothing is counted twice
+				let mut entry_reader = self.entry_reader();
+				entry_reader.follow_file_symlinks = false;
+				entry_reader.follow_dir_symlinks = false;
+				let tree = self
+					.visible_tree(
+						req,
+						&entry_path,
+						directories,
+						self.dir_size_max_depth,
+						entry_reader,
+					)
+					.await;
+				let size = tree.map_or(0, |tree| dir_size(&tree));
+				entry.size = Some(size);
+				entry.human_size = Some(self.size_units.format(size));
+			}
+		}
+
+		// Read the directory's README, if any
+		let readme = self.read_readme(req, &req_path, &directories).await;
+
+		// Prepare the directory path string
+		let directory = directory_path(&req_path);
+		// Sort the entries, preferring the client's requested sort if valid
+		let (sort_by, sort_order) = if recent.is_some() {
+			(SortBy::Modified, SortOrder::Descending)
+		} else {
+			(
+				req.query_value::<SortBy>("sort")
+					.and_then(Result::ok)
+					.unwrap_or(self.sort_by),
+				req.query_value::<SortOrder>("order")
+					.and_then(Result::ok)
+					.unwrap_or(self.sort_order),
+			)
+		};
+		match &self.comparator {
+			Some(comparator) if recent.is_none() => entry_list.sort_by(|a, b| comparator(a, b)),
+			_ => sort_entries(&mut entry_list, sort_by, sort_order, &self.sort_options),
+		}
+		// Keep only the newest files, if requested
+		if let Some(recent) = recent {
+			entry_list.truncate(recent);
+		}
+		// Guard against pathological directories, keeping the first entries
+		let overflow = self
+			.max_listing_entries
+			.filter(|&max| entry_list.len() > max);
+		if let Some(max) = overflow {
+			if let Some(status) = self.listing_overflow_status {
+				return Outcome::failure(status);
+			}
+			entry_list.truncate(max);
+		}
+		let empty = entry_list.is_empty();
+		let summary = ListingSummary::of(&entry_list);
+		// Pick the format to serve the listing in
+		let format = self.listing_format(req);
+		// Check whether the client's cached copy is still fresh
+		let validators = if self.cache_listings && !walking {
+			let variant = (
+				format,
+				sort_by,
+				sort_order,
+				req.uri().query().map(|query| query.as_str()),
+				&directory_config.title,
+			);
+			ListingValidators::new(latest_modified(&directories).await, &entry_list, &variant)
+		} else {
+			None
+		};
+		if let Some(validators) = validators.as_ref() {
+			if validators.is_fresh(req) {
+				let mut response = validators.not_modified();
+				if let Some(cache_control) = self.listing_cache_control {
+					cache_control.apply(&mut response);
+				}
+				return self
+					.served(req, &req_path, AccessKind::Listing, response)
+					.await;
+			}
+		}
+		// Keep only the requested page, if paginated
+		let pagination = req
+			.query_value::<usize>("per_page")
+			.filter(|_| !matches!(format, ListingFormat::Atom | ListingFormat::M3u))
+			.and_then(Result::ok)
+			.filter(|&per_page| per_page > 0)
+			.or(self.page_size)
+			.map(|per_page| {
+				let page = req
+					.query_value::<usize>("page")
+					.and_then(Result::ok)
+					.filter(|&page| page > 0)
+					.unwrap_or(1);
+				paginate(&mut entry_list, page, per_page)
+			});
+		// Label the entries, if configured
+		for entry in &mut entry_list {
+			self.label_entry(&req_path, entry);
+		}
+		// Add the parent directory entry, if not at the root
+		if self.show_parent_link && directory != "/" {
+			entry_list.insert(0, parent_entry());
+		}
+		// Mark directory names, if a suffix is configured
+		if let Some(suffix) = self.dir_suffix {
+			for entry in entry_list.iter_mut().filter(|entry| entry.is_dir) {
+				entry.name.push(suffix);
+				entry.display_name.push(suffix);
+			}
+		}
+		let (url, breadcrumbs) = self.breadcrumbs(req, &directory);
+		// Link directories without a trailing slash, if that's how they're served
+		if self.trailing_slash.strips_from_dirs() {
+			for entry in &mut entry_list {
+				strip_trailing_slash(entry, &breadcrumbs);
+			}
+		}
+		// Group the entries by extension, if configured
+		let groups = self
+			.group_by_extension
+			.then(|| group_by_extension(&entry_list));
+		let listing = Listing {
+			url,
+			request_path: req.uri().path().to_string(),
+			mount_base: mount_base(req).to_owned(),
+			link_query: link_query(req),
+			breadcrumbs,
+			segments: directory_segments(&directory),
+			directory,
+			entries: entry_list,
+			sort_by,
+			sort_order,
+			pagination,
+			groups,
+			title: directory_config.title,
+			search: search.map(|(_, q)| q),
+			recent,
+			readme,
+			truncated: overflow.is_some(),
+			empty,
+			summary,
+		};
+		let response = match format {
+			// Serve the feed
+			ListingFormat::Atom => (
+				FeedFormat::Atom.content_type(),
+				render_atom(&listing, &self.origin(req)),
+			)
+				.respond_to(req),
+			ListingFormat::M3u => {
+				// Serve the playlist as a download
+				let disposition = attachment_disposition(&playlist_filename(&listing));
+				let playlist = render_m3u(&listing, &self.origin(req), &self.playlist_extensions);
+				(playlist_content_type(), playlist)
+					.respond_to(req)
+					.map(|mut response| {
+						response.set_header(Header::new("Content-Disposition", disposition));
+						response
+					})
+			}
+			// Serve JSON if requested or preferred
+			ListingFormat::Json => Json(listing).respond_to(req),
+			// Serve plain text if requested or preferred
+			ListingFormat::Text => (ContentType::Plain, render_text(&listing)).respond_to(req),
+			ListingFormat::Html => {
+				if let Some(render_cache) = self.render_cache.as_ref().filter(|_| {
+					self.renderer.is_none()
+						&& self.access_filter.is_none()
+						&& self.display_name_fn.is_none()
+						&& !walking
+				}) {
+					// Reuse the built-in render if the directory hasn't changed
+					let html = render_cache.get_or_render(
+						&listing,
+						latest_modified(&directories).await,
+						|listing| render_listing(listing, self.inline_css.as_deref()),
+					);
+					Html(html).respond_to(req)
+				} else {
+					// Render the template
+					self.render_or_error(req, &req_path, listing)
+				}
+			}
+		};
+		match response {
+			Ok(mut response) => {
+				// Error pages aren't worth caching
+				if response.status().class().is_success() {
+					if let Some(validators) = validators {
+						validators.apply(&mut response);
+					}
+					if let Some(cache_control) = self.listing_cache_control {
+						cache_control.apply(&mut response);
+					}
+				}
+				if self.compression && accepts_gzip(req) {
+					compress(req, &mut response).await;
+				}
+				self.served(req, &req_path, AccessKind::Listing, response)
+					.await
+			}
+			Err(status) => Outcome::failure(status),
 		}
 	}
 }
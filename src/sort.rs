@@ -0,0 +1,182 @@
+// Uses
+use std::cmp::Ordering;
+#[cfg(feature = "collation")]
+use std::sync::Arc;
+
+#[cfg(feature = "collation")]
+use icu_collator::CollatorBorrowed;
+use rocket::{serde::Serialize, FromFormField};
+
+use crate::ListingEntry;
+
+/// The field that directory listing entries are sorted by.
+///
+/// This can be parsed from the `sort` query parameter of a listing request,
+/// using the lowercase variant name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, FromFormField)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum SortBy {
+	/// Sort by entry name.
+	#[default]
+	Name,
+	/// Sort by entry size, with entries of unknown size first.
+	Size,
+	/// Sort by last-modified time, with entries of unknown time first.
+	Modified,
+}
+
+/// The order that directory listing entries are sorted in.
+///
+/// This can be parsed from the `order` query parameter of a listing request,
+/// using either `asc`/`desc` or the lowercase variant name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, FromFormField)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum SortOrder {
+	/// Smallest values first.
+	#[default]
+	#[field(value = "asc")]
+	#[field(value = "ascending")]
+	Ascending,
+	/// Largest values first.
+	#[field(value = "desc")]
+	#[field(value = "descending")]
+	Descending,
+}
+
+/// The settings that control how entries are compared, independent of the
+/// field and order sorted by.
+#[derive(Debug, Clone)]
+pub(crate) struct SortOptions {
+	/// Whether all directories are placed before all files.
+	pub dirs_first: bool,
+	/// Whether runs of digits within names are compared numerically.
+	pub natural: bool,
+	/// Whether names are compared ignoring case.
+	pub case_insensitive: bool,
+	/// The locale-aware collator that names are compared with, if any,
+	/// instead of comparing them by their bytes.
+	#[cfg(feature = "collation")]
+	pub collator: Option<Arc<CollatorBorrowed<'static>>>,
+}
+
+impl Default for SortOptions {
+	fn default() -> Self {
+		SortOptions {
+			dirs_first: true,
+			natural: false,
+			case_insensitive: false,
+			#[cfg(feature = "collation")]
+			collator: None,
+		}
+	}
+}
+
+/// Sorts `entries` by the field `by` in the order `order`.
+///
+/// If `options.dirs_first` is set, all directories are placed before all files
+/// regardless of the order.
+pub(crate) fn sort_entries(
+	entries: &mut [ListingEntry],
+	by: SortBy,
+	order: SortOrder,
+	options: &SortOptions,
+) {
+	entries.sort_unstable_by(|a, b| {
+		let dir_ordering = if options.dirs_first {
+			b.is_dir.cmp(&a.is_dir)
+		} else {
+			Ordering::Equal
+		};
+		let name_ordering = || compare_names(&a.name, &b.name, options);
+		let field_ordering = match by {
+			SortBy::Name => name_ordering(),
+			SortBy::Size => a.size.cmp(&b.size).then_with(name_ordering),
+			SortBy::Modified => a.modified.cmp(&b.modified).then_with(name_ordering),
+		};
+		dir_ordering.then(match order {
+			SortOrder::Ascending => field_ordering,
+			SortOrder::Descending => field_ordering.reverse(),
+		})
+	});
+}
+
+/// Compares two entry names according to `options`.
+///
+/// Names that are equal under `options` fall back to a plain comparison, so
+/// the order is always deterministic.
+fn compare_names(a: &str, b: &str, options: &SortOptions) -> Ordering {
+	let ordering = if options.natural {
+		compare_natural(a, b, options)
+	} else {
+		compare_text(a, b, options)
+	};
+	ordering.then_with(|| a.cmp(b))
+}
+
+/// Compares two strings, folding case if `options.case_insensitive` is set.
+///
+/// If `options` has a collator, the strings are compared with it rather than
+/// by their bytes.
+fn compare_text(a: &str, b: &str, options: &SortOptions) -> Ordering {
+	#[cfg(feature = "collation")]
+	if let Some(collator) = &options.collator {
+		return if options.case_insensitive {
+			collator.compare(&a.to_lowercase(), &b.to_lowercase())
+		} else {
+			collator.compare(a, b)
+		};
+	}
+	if options.case_insensitive {
+		a.chars()
+			.flat_map(char::to_lowercase)
+			.cmp(b.chars().flat_map(char::to_lowercase))
+	} else {
+		a.cmp(b)
+	}
+}
+
+/// Compares two strings, treating runs of ASCII digits as numbers and folding
+/// the rest with [`compare_text`].
+///
+/// Numbers that are equal in value but differ in leading zeros (such as `001`
+/// and `1`) are ordered with fewer leading zeros first, but only once the rest
+/// of the strings compare equal, so that `a01b` still comes before `a1c`.
+fn compare_natural(a: &str, b: &str, options: &SortOptions) -> Ordering {
+	let (mut a, mut b) = (a, b);
+	let mut leading_zeros = Ordering::Equal;
+	loop {
+		let (a_run, a_rest) = split_run(a);
+		let (b_run, b_rest) = split_run(b);
+		let ordering = match (a_run, b_run) {
+			("", "") => return leading_zeros,
+			(a_run, b_run) if is_number(a_run) && is_number(b_run) => {
+				let a_digits = a_run.trim_start_matches('0');
+				let b_digits = b_run.trim_start_matches('0');
+				leading_zeros = leading_zeros.then_with(|| a_run.len().cmp(&b_run.len()));
+				a_digits
+					.len()
+					.cmp(&b_digits.len())
+					.then_with(|| a_digits.cmp(b_digits))
+			}
+			(a_run, b_run) => compare_text(a_run, b_run, options),
+		};
+		if ordering != Ordering::Equal {
+			return ordering;
+		}
+		(a, b) = (a_rest, b_rest);
+	}
+}
+
+/// Splits off the leading run of either ASCII digits or non-digits from `s`.
+fn split_run(s: &str) -> (&str, &str) {
+	let starts_with_digit = is_number(s);
+	let end = s
+		.find(|c: char| c.is_ascii_digit() != starts_with_digit)
+		.unwrap_or(s.len());
+	s.split_at(end)
+}
+
+/// Checks whether `s` starts with an ASCII digit.
+fn is_number(s: &str) -> bool {
+	s.starts_with(|c: char| c.is_ascii_digit())
+}
@@ -0,0 +1,78 @@
+// Uses
+use std::fmt::Write;
+
+use crate::{html::format_time, Listing, ListingEntry};
+
+/// Renders the entries of `listing` as plain text in the style of `ls -l`,
+/// one entry per line.
+///
+/// Each line holds the entry's mode (on Unix), human-readable size, modified
+/// time, and name, separated by spaces. Fields that aren't known are shown as
+/// `-`, or as `?`s for the mode. Control characters in names and symlink
+/// targets are escaped, so each entry stays on a line of its own.
+pub(crate) fn render_text(listing: &Listing) -> String {
+	let sizes = listing
+		.entries
+		.iter()
+		.map(|entry| entry.human_size.as_deref().unwrap_or("-").to_owned())
+		.collect::<Vec<_>>();
+	let size_width = sizes.iter().map(String::len).max().unwrap_or_default();
+
+	let mut text = String::new();
+	for (entry, size) in listing.entries.iter().zip(sizes) {
+		if cfg!(unix) {
+			let _ = write!(text, "{} ", format_mode(entry));
+		}
+		let modified = entry
+			.modified
+			.map_or_else(|| String::from('-'), format_time);
+		let _ = write!(
+			text,
+			"{size:>size_width$} {modified:<16} {}",
+			escape_control(&entry.display_name)
+		);
+		if let Some(target) = &entry.symlink_target {
+			let _ = write!(text, " -> {}", escape_control(target));
+		}
+		text.push('\n');
+	}
+	text
+}
+
+/// Escapes the control characters in `name`, such as line breaks, as Rust
+/// string escapes like `\n` and `\u{1b}`.
+fn escape_control(name: &str) -> String {
+	let mut escaped = String::with_capacity(name.len());
+	for c in name.chars() {
+		if c.is_control() {
+			escaped.extend(c.escape_default());
+		} else {
+			escaped.push(c);
+		}
+	}
+	escaped
+}
+
+/// Formats the type and permission bits of `entry` as `ls -l` does, such as
+/// `drwxr-xr-x`.
+fn format_mode(entry: &ListingEntry) -> String {
+	let kind = if entry.is_symlink {
+		'l'
+	} else if entry.is_dir {
+		'd'
+	} else {
+		'-'
+	};
+	let Some(mode) = entry.mode else {
+		return format!("{kind}?????????");
+	};
+	let mut formatted = String::with_capacity(10);
+	formatted.push(kind);
+	for shift in [6, 3, 0] {
+		let bits = mode >> shift;
+		formatted.push(if bits & 0o4 == 0 { '-' } else { 'r' });
+		formatted.push(if bits & 0o2 == 0 { '-' } else { 'w' });
+		formatted.push(if bits & 0o1 == 0 { '-' } else { 'x' });
+	}
+	formatted
+}
@@ -0,0 +1,141 @@
+// Uses
+use std::{
+	fs,
+	io::{self, Cursor},
+	path::{Path, PathBuf},
+	process,
+	sync::atomic::{AtomicU64, Ordering},
+	time::UNIX_EPOCH,
+};
+
+use image::{ImageError, ImageFormat};
+use rocket::{http::ContentType, tokio::task::spawn_blocking};
+
+/// The largest thumbnail size that may be requested, in pixels.
+pub(crate) const MAX_THUMBNAIL_SIZE: u32 = 1024;
+
+/// A thumbnail of an image, encoded and ready to serve.
+#[derive(Debug, Clone)]
+pub(crate) struct Thumbnail {
+	/// The content type of the encoded image.
+	pub content_type: ContentType,
+	/// The encoded image.
+	pub data: Vec<u8>,
+}
+
+/// Gets a thumbnail of the image at `path`, fitting within `size` pixels
+/// square and keeping its aspect ratio.
+///
+/// JPEG images get JPEG thumbnails, and every other format gets a PNG
+/// thumbnail. If `cache_dir` is set, thumbnails are read from and written to
+/// it, keyed by the image's path, size, and modification time, so that edited
+/// images get new thumbnails.
+///
+/// Returns [`None`] if the image can't be read or decoded.
+pub(crate) async fn thumbnail(
+	path: PathBuf,
+	size: u32,
+	cache_dir: Option<PathBuf>,
+) -> Option<Thumbnail> {
+	spawn_blocking(move || {
+		let format = ImageFormat::from_path(&path).ok()?;
+		let (format, content_type) = if format == ImageFormat::Jpeg {
+			(ImageFormat::Jpeg, ContentType::JPEG)
+		} else {
+			(ImageFormat::Png, ContentType::PNG)
+		};
+		let cached = cache_dir
+			.as_deref()
+			.and_then(|cache_dir| cache_path(cache_dir, &path, size, format));
+		if let Some(data) = cached.as_ref().and_then(|cached| fs::read(cached).ok()) {
+			return Some(Thumbnail { content_type, data });
+		}
+
+		let data = render(&path, size, format).ok()?;
+		if let Some(cached) = cached {
+			// A failure to cache only costs a later rebuild
+			let _ = write_atomically(&cached, &data);
+		}
+		Some(Thumbnail { content_type, data })
+	})
+	.await
+	.ok()
+	.flatten()
+}
+
+/// Decodes the image at `path` and encodes a thumbnail of it as `format`.
+fn render(path: &Path, size: u32, format: ImageFormat) -> Result<Vec<u8>, ImageError> {
+	let image = image::open(path)?.thumbnail(size, size);
+	let image = if format == ImageFormat::Jpeg {
+		image.into_rgb8().into()
+	} else {
+		image
+	};
+	let mut data = Vec::new();
+	image.write_to(&mut Cursor::new(&mut data), format)?;
+	Ok(data)
+}
+
+/// Builds the path in `cache_dir` of the thumbnail of `path`.
+///
+/// The name is a hash of the canonical path, length, and modified time of the
+/// image, which is stable across Rust releases so the cache survives them.
+///
+/// Returns [`None`] if the image's metadata can't be read.
+fn cache_path(cache_dir: &Path, path: &Path, size: u32, format: ImageFormat) -> Option<PathBuf> {
+	let metadata = fs::metadata(path).ok()?;
+	let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+	let mut hash = Fnv1a::default();
+	hash.write(path.canonicalize().ok()?.as_os_str().as_encoded_bytes());
+	hash.write(&metadata.len().to_le_bytes());
+	hash.write(&modified.as_secs().to_le_bytes());
+	hash.write(&modified.subsec_nanos().to_le_bytes());
+	let extension = format.extensions_str().first()?;
+	Some(cache_dir.join(format!("{:016x}-{size}.{extension}", hash.0)))
+}
+
+/// The 64-bit FNV-1a hash, which unlike the standard library's hashers is
+/// guaranteed not to change.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+	fn default() -> Self {
+		Fnv1a(0xcbf2_9ce4_8422_2325)
+	}
+}
+
+impl Fnv1a {
+	/// Adds `bytes` to the hash.
+	fn write(&mut self, bytes: &[u8]) {
+		for &byte in bytes {
+			self.0 ^= u64::from(byte);
+			self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+		}
+	}
+}
+
+/// Writes `data` to `path`, so that it's never seen partially written.
+///
+/// The data is written to a temporary file beside `path`, named uniquely so
+/// that concurrent writes of the same thumbnail don't share it, and then
+/// renamed into place.
+fn write_atomically(path: &Path, data: &[u8]) -> io::Result<()> {
+	/// Distinguishes the temporary files of concurrent writes in this process.
+	static WRITES: AtomicU64 = AtomicU64::new(0);
+
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent)?;
+	}
+	let mut partial = path.as_os_str().to_owned();
+	partial.push(format!(
+		".{}.{}.partial",
+		process::id(),
+		WRITES.fetch_add(1, Ordering::Relaxed)
+	));
+	let partial = PathBuf::from(partial);
+	let written = fs::write(&partial, data).and_then(|()| fs::rename(&partial, path));
+	if written.is_err() {
+		let _ = fs::remove_file(&partial);
+	}
+	written
+}
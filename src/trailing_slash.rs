@@ -0,0 +1,54 @@
+/// How requests are redirected to normalize the trailing slashes of their
+/// URLs, as set with [`crate::ListingFileServer::trailing_slash`].
+///
+/// Redirects are permanent by default, and keep the query as-is. They're
+/// made with a [`rocket::http::Status::MovedPermanently`], like most web
+/// servers do, or temporary with
+/// [`crate::ListingFileServer::normalize_redirect_permanent`]. Directories
+/// that only have a slash added because of
+/// [`rocket::fs::Options::NormalizeDirs`] are redirected with a
+/// [`rocket::http::Status::PermanentRedirect`] instead, like
+/// [`rocket::fs::FileServer`] does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TrailingSlash {
+	/// Leave URLs as they're requested, unless
+	/// [`rocket::fs::Options::NormalizeDirs`] is enabled.
+	None,
+	/// Strip the trailing slash from file URLs that have one, leaving
+	/// directory URLs as they're requested.
+	///
+	/// This is the default, since the relative links of a file requested with
+	/// a trailing slash resolve against the wrong directory.
+	#[default]
+	StripFromFiles,
+	/// Add a trailing slash to directory URLs that lack one, the same as
+	/// [`rocket::fs::Options::NormalizeDirs`], and strip it from file URLs
+	/// that have one.
+	AddToDirs,
+	/// Strip the trailing slash from every URL that has one, apart from the
+	/// root of the server, whose URL is where it's mounted.
+	///
+	/// This takes precedence over [`rocket::fs::Options::NormalizeDirs`].
+	/// Listings link to directories without a trailing slash, and the built-in
+	/// page resolves its links against the directory with a `<base>` element,
+	/// so custom renderers should link relative to [`crate::Listing::url`] the
+	/// same way.
+	StripFromAll,
+}
+
+impl TrailingSlash {
+	/// Whether directory URLs get a trailing slash added.
+	pub(crate) fn adds_to_dirs(self) -> bool {
+		self == TrailingSlash::AddToDirs
+	}
+
+	/// Whether directory URLs get their trailing slash stripped.
+	pub(crate) fn strips_from_dirs(self) -> bool {
+		self == TrailingSlash::StripFromAll
+	}
+
+	/// Whether file URLs get their trailing slash stripped.
+	pub(crate) fn strips_from_files(self) -> bool {
+		self != TrailingSlash::None
+	}
+}
@@ -0,0 +1,78 @@
+// Uses
+use std::{
+	io::{self, Write},
+	path::{Component, Path},
+};
+
+use glob::Pattern;
+use rocket::tokio::{
+	io::{AsyncWriteExt, DuplexStream},
+	runtime::Handle,
+};
+
+/// Checks whether `path` resolves to a location within `root`.
+///
+/// Paths that can't be resolved are treated as outside of the root.
+pub(crate) fn is_within_root(path: &Path, root: &Path) -> bool {
+	match (path.canonicalize(), root.canonicalize()) {
+		(Ok(path), Ok(root)) => path.starts_with(root),
+		_ => false,
+	}
+}
+
+/// Checks whether `path` is made up of plain names only, so that joining it
+/// onto a root can't escape that root.
+///
+/// Rocket already rejects traversal when parsing request segments, so this is
+/// only a second line of defence against `..`, absolute paths, and drive
+/// prefixes.
+pub(crate) fn is_plain_relative(path: &Path) -> bool {
+	path.components()
+		.all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Checks whether the entry name `name`, of a directory if `is_dir` is set,
+/// matches any of `include_patterns`.
+///
+/// Every entry is included if there are no patterns, and directories are
+/// included regardless if `always_include_dirs` is set.
+pub(crate) fn is_included(
+	name: &str,
+	is_dir: bool,
+	include_patterns: &[Pattern],
+	always_include_dirs: bool,
+) -> bool {
+	include_patterns.is_empty()
+		|| (is_dir && always_include_dirs)
+		|| include_patterns.iter().any(|pattern| pattern.matches(name))
+}
+
+/// An adapter that allows a blocking thread to write to an asynchronous
+/// stream.
+pub(crate) struct BlockingWriter {
+	inner: DuplexStream,
+	handle: Handle,
+}
+
+impl BlockingWriter {
+	/// Wraps `inner`, which is written to on the current runtime.
+	///
+	/// This must be called from within the runtime, before moving to the
+	/// blocking thread.
+	pub(crate) fn new(inner: DuplexStream) -> Self {
+		BlockingWriter {
+			inner,
+			handle: Handle::current(),
+		}
+	}
+}
+
+impl Write for BlockingWriter {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.handle.block_on(self.inner.write(buf))
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.handle.block_on(self.inner.flush())
+	}
+}
@@ -0,0 +1,224 @@
+//! Downloads of directories as archives.
+
+// Modules
+mod common;
+
+// Uses
+use std::{
+	collections::HashMap,
+	io::{Cursor, Read},
+	path::{Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+use listing_file_server::ListingFileServer;
+use rocket::{
+	fs::Options,
+	http::{ContentType, Status},
+};
+use tar::{Archive, EntryType};
+use zip::ZipArchive;
+
+use crate::common::{client, root_with};
+
+/// Reads the files of the zip archive `body`, by path.
+fn unzip(body: Vec<u8>) -> HashMap<String, Vec<u8>> {
+	let mut archive = ZipArchive::new(Cursor::new(body)).expect("the body is a zip archive");
+	(0..archive.len())
+		.map(|index| {
+			let mut file = archive.by_index(index).expect("the entry can be read");
+			let mut contents = Vec::new();
+			file.read_to_end(&mut contents)
+				.expect("the contents can be read");
+			(
+				file.name().expect("the name is UTF-8").into_owned(),
+				contents,
+			)
+		})
+		.collect()
+}
+
+#[test]
+fn directories_are_downloaded_as_zip_archives() {
+	let root = root_with(&[
+		("docs/guide.txt", b"guide"),
+		("docs/api/index.txt", b"index"),
+		("docs/empty/", b""),
+	]);
+	let client = client(ListingFileServer::from_path(root.path()).enable_archive_download(true));
+
+	let response = client.get("/docs/?download=zip").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.content_type(), Some(ContentType::ZIP));
+	let disposition = response.headers().get_one("Content-Disposition");
+	assert!(
+		disposition
+			.is_some_and(|disposition| disposition.starts_with("attachment; filename=\"docs.zip\"")),
+		"{disposition:?}"
+	);
+	let files = unzip(response.into_bytes().expect("the archive has a body"));
+	assert_eq!(files["guide.txt"], b"guide");
+	assert_eq!(files["api/index.txt"], b"index");
+	assert!(files.contains_key("api/"));
+	assert!(files.contains_key("empty/"));
+	assert_eq!(files.len(), 4, "{:?}", files.keys());
+}
+
+/// Gets the sorted paths in the zip archive of the root of `server`.
+fn zipped_names(server: ListingFileServer) -> Vec<String> {
+	let client = client(server);
+	let response = client.get("/?download=zip").dispatch();
+	let mut names = unzip(response.into_bytes().expect("the archive has a body"))
+		.into_keys()
+		.collect::<Vec<_>>();
+	names.sort_unstable();
+	names
+}
+
+#[test]
+fn zip_archives_only_include_dotfiles_if_enabled() {
+	let root = root_with(&[
+		("visible.txt", b""),
+		(".hidden.txt", b""),
+		(".config/settings.txt", b""),
+	]);
+	let server = ListingFileServer::from_path(root.path()).enable_archive_download(true);
+
+	assert_eq!(zipped_names(server.clone()), ["visible.txt"]);
+	assert_eq!(
+		zipped_names(server.add_option(Options::DotFiles)),
+		[
+			".config/",
+			".config/settings.txt",
+			".hidden.txt",
+			"visible.txt"
+		]
+	);
+}
+
+#[test]
+fn directories_are_not_downloaded_unless_enabled() {
+	let root = root_with(&[("docs/guide.txt", b"guide")]);
+	let client = client(ListingFileServer::from_path(root.path()));
+
+	let response = client.get("/docs/?download=zip").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.content_type(), Some(ContentType::HTML));
+	assert_eq!(response.headers().get_one("Content-Disposition"), None);
+}
+
+/// An entry unpacked from a tar archive.
+#[derive(Debug)]
+struct TarEntry {
+	entry_type: EntryType,
+	mode: u32,
+	link_name: Option<PathBuf>,
+	contents: Vec<u8>,
+}
+
+/// Reads the entries of the gzip-compressed tar archive `body`, by path.
+fn untar(body: &[u8]) -> HashMap<String, TarEntry> {
+	let mut archive = Archive::new(GzDecoder::new(body));
+	archive
+		.entries()
+		.expect("the body is a tar archive")
+		.map(|entry| {
+			let mut entry = entry.expect("the entry can be read");
+			let path = entry.path().expect("the entry has a path");
+			let path = path.to_str().expect("the path is UTF-8").to_owned();
+			let header = entry.header();
+			let entry_type = header.entry_type();
+			let mode = header.mode().expect("the entry has a mode");
+			let link_name = header
+				.link_name()
+				.expect("the link name can be read")
+				.map(|link_name| link_name.into_owned());
+			let mut contents = Vec::new();
+			entry
+				.read_to_end(&mut contents)
+				.expect("the contents can be read");
+			(
+				path,
+				TarEntry {
+					entry_type,
+					mode,
+					link_name,
+					contents,
+				},
+			)
+		})
+		.collect()
+}
+
+#[cfg(unix)]
+#[test]
+fn tar_archives_keep_modes_and_symlinks_within_the_archive() {
+	use std::{
+		fs::{set_permissions, Permissions},
+		os::unix::fs::{symlink, PermissionsExt},
+	};
+
+	let root = root_with(&[("notes.txt", b"notes"), ("docs/guide.txt", b"guide")]);
+	let outside = root_with(&[("secret.txt", b"outside")]);
+	let path = |name: &str| root.path().join(name);
+	set_permissions(path("notes.txt"), Permissions::from_mode(0o754))
+		.expect("the permissions can be set");
+	symlink("notes.txt", path("latest.txt")).expect("the symlink can be created");
+	symlink("docs", path("manual")).expect("the symlink can be created");
+	symlink(outside.path().join("secret.txt"), path("elsewhere.txt"))
+		.expect("the symlink can be created");
+	symlink(Path::new("..").join("notes.txt"), path("docs/up.txt"))
+		.expect("the symlink can be created");
+	let client = client(ListingFileServer::from_path(root.path()).enable_archive_download(true));
+
+	let response = client.get("/?download=tar.gz").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.content_type(), Some(ContentType::GZIP));
+	let entries = untar(&response.into_bytes().expect("the archive has a body"));
+
+	let notes = &entries["notes.txt"];
+	assert_eq!(notes.entry_type, EntryType::Regular);
+	assert_eq!(notes.mode & 0o777, 0o754);
+	assert_eq!(notes.contents, b"notes");
+
+	// Symlinks to archived entries stay symlinks
+	for (link, target) in [
+		("latest.txt", "notes.txt"),
+		("manual", "docs"),
+		("docs/up.txt", "../notes.txt"),
+	] {
+		assert_eq!(entries[link].entry_type, EntryType::Symlink, "{link}");
+		assert_eq!(
+			entries[link].link_name.as_deref(),
+			Some(Path::new(target)),
+			"{link}"
+		);
+	}
+	assert!(!entries.contains_key("manual/guide.txt"));
+	assert_eq!(entries["docs/guide.txt"].contents, b"guide");
+
+	// Symlinks out of the archive are followed
+	let elsewhere = &entries["elsewhere.txt"];
+	assert_eq!(elsewhere.entry_type, EntryType::Regular);
+	assert_eq!(elsewhere.contents, b"outside");
+}
+
+#[cfg(unix)]
+#[test]
+fn tar_archives_of_subdirectories_follow_symlinks_out_of_them() {
+	use std::os::unix::fs::symlink;
+
+	let root = root_with(&[("notes.txt", b"notes"), ("docs/guide.txt", b"guide")]);
+	symlink(
+		Path::new("..").join("notes.txt"),
+		root.path().join("docs/up.txt"),
+	)
+	.expect("the symlink can be created");
+	let client = client(ListingFileServer::from_path(root.path()).enable_archive_download(true));
+
+	let response = client.get("/docs/?download=tar.gz").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let entries = untar(&response.into_bytes().expect("the archive has a body"));
+	assert_eq!(entries["up.txt"].entry_type, EntryType::Regular);
+	assert_eq!(entries["up.txt"].contents, b"notes");
+}
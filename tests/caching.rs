@@ -0,0 +1,173 @@
+//! Conditional requests for files and listings.
+
+// Modules
+mod common;
+
+// Uses
+use std::fs;
+
+use listing_file_server::ListingFileServer;
+use rocket::{
+	http::{Header, Status},
+	local::blocking::Client,
+};
+
+use crate::common::{client, client_for, root_with};
+
+#[test]
+fn unchanged_files_are_not_modified() {
+	let root = root_with(&[("file.txt", b"contents")]);
+	let client = client_for(root.path());
+
+	let response = client.get("/file.txt").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let last_modified = response
+		.headers()
+		.get_one("Last-Modified")
+		.expect("files are served with their modified time")
+		.to_owned();
+
+	let response = client
+		.get("/file.txt")
+		.header(Header::new("If-Modified-Since", last_modified.clone()))
+		.dispatch();
+	assert_eq!(response.status(), Status::NotModified);
+	assert_eq!(
+		response.headers().get_one("Last-Modified"),
+		Some(last_modified.as_str())
+	);
+	assert!(response.into_bytes().unwrap_or_default().is_empty());
+}
+
+#[test]
+fn files_modified_since_are_served() {
+	let root = root_with(&[("file.txt", b"contents")]);
+	let client = client_for(root.path());
+
+	let response = client
+		.get("/file.txt")
+		.header(Header::new(
+			"If-Modified-Since",
+			"Thu, 01 Jan 1970 00:00:00 GMT",
+		))
+		.dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"contents"[..]));
+}
+
+#[test]
+fn if_none_match_takes_precedence_for_files() {
+	let root = root_with(&[("file.txt", b"contents")]);
+	let client = client_for(root.path());
+
+	let last_modified = client
+		.get("/file.txt")
+		.dispatch()
+		.headers()
+		.get_one("Last-Modified")
+		.expect("files are served with their modified time")
+		.to_owned();
+	// Files have no entity tag, so nothing can match
+	let response = client
+		.get("/file.txt")
+		.header(Header::new("If-Modified-Since", last_modified))
+		.header(Header::new("If-None-Match", "\"file\""))
+		.dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"contents"[..]));
+}
+
+#[test]
+fn unchanged_listings_are_not_modified() {
+	let root = root_with(&[("file.txt", b"contents")]);
+	let client = client(ListingFileServer::from_path(root.path()).cache_listings(true));
+
+	let response = client.get("/").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let etag = response
+		.headers()
+		.get_one("ETag")
+		.expect("cached listings have an entity tag")
+		.to_owned();
+
+	let response = client
+		.get("/")
+		.header(Header::new("If-None-Match", etag.clone()))
+		.dispatch();
+	assert_eq!(response.status(), Status::NotModified);
+
+	let response = client
+		.get("/")
+		.header(Header::new("If-None-Match", "W/\"stale\""))
+		.dispatch();
+	assert_eq!(response.status(), Status::Ok);
+
+	// Other representations of the listing have their own entity tags
+	let response = client
+		.get("/?sort=size")
+		.header(Header::new("If-None-Match", etag))
+		.dispatch();
+	assert_eq!(response.status(), Status::Ok);
+}
+
+/// Gets the HTML listing at `uri`.
+fn listing(client: &Client, uri: &str) -> String {
+	let response = client.get(uri).dispatch();
+	assert_eq!(response.status(), Status::Ok, "{uri}");
+	response.into_string().expect("the listing is UTF-8")
+}
+
+#[test]
+fn cached_renders_are_reused_while_unchanged() {
+	let root = root_with(&[("a.txt", b"a"), ("docs/b.txt", b"b")]);
+	let cached = client(ListingFileServer::from_path(root.path()).render_cache(4));
+	let uncached = client_for(root.path());
+
+	for uri in [
+		"/",
+		"/docs/",
+		"/?sort=size&order=desc",
+		"/?per_page=1&page=2",
+	] {
+		let first = listing(&cached, uri);
+		assert_eq!(listing(&cached, uri), first, "{uri}");
+		assert_eq!(listing(&uncached, uri), first, "{uri}");
+	}
+}
+
+#[test]
+fn cached_renders_are_replaced_once_changed() {
+	let root = root_with(&[("a.txt", b"a")]);
+	let client = client(ListingFileServer::from_path(root.path()).render_cache(1));
+
+	assert!(!listing(&client, "/").contains("added.txt"));
+	fs::write(root.path().join("added.txt"), b"").expect("the file can be written");
+	assert!(listing(&client, "/").contains("added.txt"));
+
+	// Sizes are compared too, whether or not the directory itself changed
+	let before = listing(&client, "/");
+	fs::write(root.path().join("a.txt"), vec![b'a'; 4096]).expect("the file can be written");
+	let after = listing(&client, "/");
+	assert_ne!(after, before);
+	assert_eq!(after, listing(&client_for(root.path()), "/"));
+
+	fs::remove_file(root.path().join("added.txt")).expect("the file can be removed");
+	assert!(!listing(&client, "/").contains("added.txt"));
+}
+
+#[test]
+fn renders_are_cached_per_sort_and_page() {
+	let root = root_with(&[("a.txt", b"aaa"), ("b.txt", b"b")]);
+	let client = client(ListingFileServer::from_path(root.path()).render_cache(4));
+
+	let by_name = listing(&client, "/");
+	let by_size = listing(&client, "/?sort=size");
+	assert!(by_name.find("a.txt") < by_name.find("b.txt"));
+	assert!(by_size.find("b.txt") < by_size.find("a.txt"));
+	assert_eq!(listing(&client, "/"), by_name);
+
+	let first_page = listing(&client, "/?per_page=1");
+	let second_page = listing(&client, "/?per_page=1&page=2");
+	assert!(first_page.contains("a.txt") && !first_page.contains("b.txt"));
+	assert!(second_page.contains("b.txt") && !second_page.contains("a.txt"));
+}
@@ -0,0 +1,75 @@
+//! Sorting names with a locale's collation rules.
+#![cfg(feature = "collation")]
+
+// Modules
+mod common;
+
+// Uses
+use listing_file_server::ListingFileServer;
+use rocket::{http::Status, local::blocking::Client, serde::json::Value};
+
+use crate::common::{client, root_with};
+
+const FILES: &[(&str, &[u8])] = &[
+	("zebra.txt", b""),
+	("éclair.txt", b""),
+	("apple.txt", b""),
+	("eagle.txt", b""),
+	("Étude.txt", b""),
+];
+
+/// Gets the names of the entries of the root listing, in order.
+fn listed_names(client: &Client) -> Vec<String> {
+	let response = client.get("/?format=json").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let listing = response.into_json::<Value>().expect("the listing is JSON");
+	listing["entries"]
+		.as_array()
+		.expect("the listing has entries")
+		.iter()
+		.map(|entry| {
+			entry["name"]
+				.as_str()
+				.expect("entries are named")
+				.to_owned()
+		})
+		.collect()
+}
+
+#[test]
+fn names_are_compared_by_bytes_by_default() {
+	let root = root_with(FILES);
+	let client = client(ListingFileServer::from_path(root.path()).enable_json(true));
+
+	assert_eq!(
+		listed_names(&client),
+		[
+			"apple.txt",
+			"eagle.txt",
+			"zebra.txt",
+			"Étude.txt",
+			"éclair.txt"
+		]
+	);
+}
+
+#[test]
+fn accented_names_sort_alongside_their_base_letters() {
+	let root = root_with(FILES);
+	let client = client(
+		ListingFileServer::from_path(root.path())
+			.enable_json(true)
+			.collation("fr"),
+	);
+
+	assert_eq!(
+		listed_names(&client),
+		[
+			"apple.txt",
+			"eagle.txt",
+			"éclair.txt",
+			"Étude.txt",
+			"zebra.txt"
+		]
+	);
+}
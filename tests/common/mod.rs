@@ -0,0 +1,51 @@
+//! Helpers shared by the integration tests.
+
+// Each test crate only uses some of these
+#![allow(dead_code)]
+
+// Uses
+use std::{fs, path::Path};
+
+use listing_file_server::ListingFileServer;
+use rocket::{config::LogLevel, local::blocking::Client, Config};
+use tempfile::TempDir;
+
+/// Creates a temporary directory holding `files`, as `(path, contents)` pairs
+/// relative to it.
+///
+/// Paths ending in a `/` are created as empty directories. Parent directories
+/// are created as needed.
+pub fn root_with(files: &[(&str, &[u8])]) -> TempDir {
+	let root = tempfile::tempdir().expect("a temporary directory can be created");
+	for (path, contents) in files {
+		let path_on_disk = root.path().join(path);
+		if path.ends_with('/') {
+			fs::create_dir_all(&path_on_disk).expect("the directory can be created");
+			continue;
+		}
+		if let Some(parent) = path_on_disk.parent() {
+			fs::create_dir_all(parent).expect("the parent directory can be created");
+		}
+		fs::write(&path_on_disk, contents).expect("the file can be written");
+	}
+	root
+}
+
+/// Creates a client for `server`, mounted at `/`.
+pub fn client(server: ListingFileServer) -> Client {
+	client_at("/", server)
+}
+
+/// Creates a client for `server`, mounted at `base`.
+pub fn client_at(base: &str, server: ListingFileServer) -> Client {
+	let config = Config {
+		log_level: LogLevel::Off,
+		..Config::debug_default()
+	};
+	Client::untracked(rocket::custom(config).mount(base, server)).expect("the server launches")
+}
+
+/// Creates a client for the built-in HTML listings of `root`, mounted at `/`.
+pub fn client_for(root: &Path) -> Client {
+	client(ListingFileServer::from_path(root))
+}
@@ -0,0 +1,275 @@
+//! Compression of responses on the fly.
+
+// Modules
+mod common;
+
+// Uses
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+use listing_file_server::ListingFileServer;
+use rocket::{
+	http::{ContentType, Header, Status},
+	local::blocking::{Client, LocalResponse},
+};
+
+use crate::common::{client, root_with};
+
+/// Creates a client for `root` with compression enabled.
+fn compressing_client(root: &std::path::Path) -> Client {
+	client(ListingFileServer::from_path(root).compression(true))
+}
+
+/// Gets the headers of `response` that describe its body, for comparing the
+/// responses to different methods.
+fn body_headers(response: &LocalResponse<'_>) -> Vec<(String, Option<String>)> {
+	[
+		"Content-Type",
+		"Content-Encoding",
+		"Vary",
+		"Accept-Ranges",
+		"ETag",
+	]
+	.into_iter()
+	.map(|name| {
+		let value = response.headers().get_one(name).map(str::to_owned);
+		(name.to_owned(), value)
+	})
+	.collect()
+}
+
+#[test]
+fn head_matches_get_when_compressed() {
+	let contents = "compressible text\n".repeat(256);
+	let root = root_with(&[("big.txt", contents.as_bytes())]);
+	let client = compressing_client(root.path());
+
+	for uri in ["/big.txt", "/"] {
+		let get = client
+			.get(uri)
+			.header(Header::new("Accept-Encoding", "gzip"))
+			.dispatch();
+		let head = client
+			.head(uri)
+			.header(Header::new("Accept-Encoding", "gzip"))
+			.dispatch();
+		assert_eq!(get.status(), Status::Ok);
+		assert_eq!(head.status(), Status::Ok);
+		assert_eq!(get.headers().get_one("Content-Encoding"), Some("gzip"));
+		assert_eq!(body_headers(&head), body_headers(&get), "{uri}");
+		// Neither has a length, since the compressed size isn't known up front
+		assert_eq!(get.body().preset_size(), None, "{uri}");
+		assert_eq!(head.body().preset_size(), None, "{uri}");
+		assert!(head.into_bytes().unwrap_or_default().is_empty());
+	}
+}
+
+#[test]
+fn head_matches_get_when_not_compressed() {
+	let contents = "compressible text\n".repeat(256);
+	let root = root_with(&[("big.txt", contents.as_bytes())]);
+	let client = compressing_client(root.path());
+
+	let get = client.get("/big.txt").dispatch();
+	let head = client.head("/big.txt").dispatch();
+	assert_eq!(get.headers().get_one("Content-Encoding"), None);
+	assert_eq!(body_headers(&head), body_headers(&get));
+}
+
+/// Decompresses the gzip-encoded `body`.
+fn gunzip(body: &[u8]) -> Vec<u8> {
+	let mut decompressed = Vec::new();
+	GzDecoder::new(body)
+		.read_to_end(&mut decompressed)
+		.expect("the body is valid gzip");
+	decompressed
+}
+
+#[test]
+fn text_files_are_compressed() {
+	let contents = "compressible text\n".repeat(256);
+	let root = root_with(&[("big.txt", contents.as_bytes())]);
+	let client = compressing_client(root.path());
+
+	let response = client
+		.get("/big.txt")
+		.header(Header::new("Accept-Encoding", "deflate, gzip;q=0.5"))
+		.dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+	assert!(response
+		.headers()
+		.get("Vary")
+		.any(|vary| vary.contains("Accept-Encoding")));
+	let body = response.into_bytes().expect("the file has a body");
+	assert!(body.len() < contents.len());
+	assert_eq!(gunzip(&body), contents.as_bytes());
+}
+
+#[test]
+fn listings_are_compressed() {
+	let names = (0..100)
+		.map(|i| format!("file number {i}.txt"))
+		.collect::<Vec<_>>();
+	let files = names
+		.iter()
+		.map(|name| (name.as_str(), &b""[..]))
+		.collect::<Vec<_>>();
+	let root = root_with(&files);
+	let client = compressing_client(root.path());
+
+	let response = client
+		.get("/")
+		.header(Header::new("Accept-Encoding", "gzip"))
+		.dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+	let body = gunzip(&response.into_bytes().expect("the listing has a body"));
+	let body = String::from_utf8(body).expect("the listing is UTF-8");
+	assert!(body.contains("file number 99.txt"));
+}
+
+#[test]
+fn compressed_formats_are_not_compressed_again() {
+	let contents = vec![0; 4096];
+	let root = root_with(&[("image.png", &contents), ("archive.zip", &contents)]);
+	let client = compressing_client(root.path());
+
+	for uri in ["/image.png", "/archive.zip"] {
+		let response = client
+			.get(uri)
+			.header(Header::new("Accept-Encoding", "gzip"))
+			.dispatch();
+		assert_eq!(response.status(), Status::Ok);
+		assert_eq!(
+			response.headers().get_one("Content-Encoding"),
+			None,
+			"{uri}"
+		);
+		assert_eq!(response.into_bytes(), Some(contents.clone()), "{uri}");
+	}
+}
+
+#[test]
+fn small_bodies_are_not_compressed() {
+	let root = root_with(&[("small.txt", b"small")]);
+	let client = compressing_client(root.path());
+
+	let response = client
+		.get("/small.txt")
+		.header(Header::new("Accept-Encoding", "gzip"))
+		.dispatch();
+	assert_eq!(response.headers().get_one("Content-Encoding"), None);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"small"[..]));
+}
+
+#[test]
+fn responses_are_not_compressed_unless_accepted() {
+	let contents = "compressible text\n".repeat(256);
+	let root = root_with(&[("big.txt", contents.as_bytes())]);
+	let client = compressing_client(root.path());
+
+	let accept_encodings = [None, Some("identity"), Some("gzip;q=0"), Some("br")];
+	for accept_encoding in accept_encodings {
+		let mut request = client.get("/big.txt");
+		if let Some(accept_encoding) = accept_encoding {
+			request.add_header(Header::new("Accept-Encoding", accept_encoding));
+		}
+		let response = request.dispatch();
+		assert_eq!(response.status(), Status::Ok);
+		assert_eq!(
+			response.headers().get_one("Content-Encoding"),
+			None,
+			"{accept_encoding:?}"
+		);
+		assert_eq!(
+			response.into_bytes().as_deref(),
+			Some(contents.as_bytes()),
+			"{accept_encoding:?}"
+		);
+	}
+}
+
+#[test]
+fn responses_are_not_compressed_unless_enabled() {
+	let contents = "compressible text\n".repeat(256);
+	let root = root_with(&[("big.txt", contents.as_bytes())]);
+	let client = client(ListingFileServer::from_path(root.path()));
+
+	let response = client
+		.get("/big.txt")
+		.header(Header::new("Accept-Encoding", "gzip"))
+		.dispatch();
+	assert_eq!(response.headers().get_one("Content-Encoding"), None);
+	assert_eq!(response.into_bytes().as_deref(), Some(contents.as_bytes()));
+}
+
+/// Creates a directory holding `app.js` and `style.css`, alongside their
+/// precompressed siblings.
+fn precompressed_root() -> tempfile::TempDir {
+	root_with(&[
+		("app.js", b"original"),
+		("app.js.br", b"brotli"),
+		("app.js.gz", b"gzip"),
+		("style.css", b"original"),
+		("style.css.gz", b"gzip"),
+	])
+}
+
+#[test]
+fn precompressed_files_are_served_if_accepted() {
+	let root = precompressed_root();
+	let client = client(ListingFileServer::from_path(root.path()).precompressed(true));
+
+	let cases = [
+		("/app.js", "gzip, br", Some("br"), &b"brotli"[..]),
+		("/app.js", "gzip", Some("gzip"), b"gzip"),
+		("/app.js", "br;q=0, gzip", Some("gzip"), b"gzip"),
+		("/app.js", "identity", None, b"original"),
+		("/style.css", "br, gzip", Some("gzip"), b"gzip"),
+		("/style.css", "br", None, b"original"),
+	];
+	for (uri, accept_encoding, encoding, body) in cases {
+		let response = client
+			.get(uri)
+			.header(Header::new("Accept-Encoding", accept_encoding))
+			.dispatch();
+		assert_eq!(response.status(), Status::Ok);
+		let context = format!("{uri} {accept_encoding}");
+		assert_eq!(
+			response.headers().get_one("Content-Encoding"),
+			encoding,
+			"{context}"
+		);
+		assert_eq!(
+			response.headers().get_one("Vary"),
+			Some("Accept-Encoding"),
+			"{context}"
+		);
+		let content_type = if uri == "/app.js" {
+			ContentType::JavaScript
+		} else {
+			ContentType::CSS
+		};
+		assert_eq!(response.content_type(), Some(content_type), "{context}");
+		assert_eq!(response.into_bytes().as_deref(), Some(body), "{context}");
+	}
+
+	let response = client.get("/app.js").dispatch();
+	assert_eq!(response.headers().get_one("Content-Encoding"), None);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"original"[..]));
+}
+
+#[test]
+fn precompressed_files_are_not_served_unless_enabled() {
+	let root = precompressed_root();
+	let client = client(ListingFileServer::from_path(root.path()));
+
+	let response = client
+		.get("/app.js")
+		.header(Header::new("Accept-Encoding", "br, gzip"))
+		.dispatch();
+	assert_eq!(response.headers().get_one("Content-Encoding"), None);
+	assert_eq!(response.headers().get_one("Vary"), None);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"original"[..]));
+}
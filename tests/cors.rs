@@ -0,0 +1,104 @@
+//! Cross-origin resource sharing.
+
+// Modules
+mod common;
+
+// Uses
+use std::time::Duration;
+
+use listing_file_server::{CorsConfig, ListingFileServer};
+use rocket::http::{Header, Method, Status};
+
+use crate::common::{client, client_for, root_with};
+
+#[test]
+fn preflights_from_allowed_origins_are_answered() {
+	let root = root_with(&[("file.txt", b"")]);
+	let cors = CorsConfig::new(&["https://example.com"])
+		.methods(&[Method::Get])
+		.headers(&["Range"])
+		.max_age(Duration::from_secs(600));
+	let client = client(ListingFileServer::from_path(root.path()).cors(cors));
+
+	for uri in ["/", "/file.txt"] {
+		let response = client
+			.options(uri)
+			.header(Header::new("Origin", "https://EXAMPLE.com"))
+			.header(Header::new("Access-Control-Request-Method", "GET"))
+			.dispatch();
+		assert_eq!(response.status(), Status::NoContent, "{uri}");
+		let headers = response.headers();
+		assert_eq!(headers.get_one("Allow"), Some("GET, HEAD, OPTIONS"));
+		assert_eq!(
+			headers.get_one("Access-Control-Allow-Origin"),
+			Some("https://EXAMPLE.com")
+		);
+		assert_eq!(headers.get_one("Access-Control-Allow-Methods"), Some("GET"));
+		assert_eq!(
+			headers.get_one("Access-Control-Allow-Headers"),
+			Some("Range")
+		);
+		assert_eq!(headers.get_one("Access-Control-Max-Age"), Some("600"));
+		assert_eq!(headers.get_one("Vary"), Some("Origin"));
+	}
+}
+
+#[test]
+fn preflights_from_other_origins_are_not_allowed() {
+	let root = root_with(&[]);
+	let cors = CorsConfig::new(&["https://example.com"]);
+	let client = client(ListingFileServer::from_path(root.path()).cors(cors));
+
+	let response = client
+		.options("/")
+		.header(Header::new("Origin", "https://example.org"))
+		.header(Header::new("Access-Control-Request-Method", "GET"))
+		.dispatch();
+	assert_eq!(response.status(), Status::NoContent);
+	let headers = response.headers();
+	assert_eq!(headers.get_one("Allow"), Some("GET, HEAD, OPTIONS"));
+	assert_eq!(headers.get_one("Access-Control-Allow-Origin"), None);
+	assert_eq!(headers.get_one("Access-Control-Allow-Methods"), None);
+}
+
+#[test]
+fn responses_to_allowed_origins_allow_them() {
+	let root = root_with(&[("file.txt", b"contents")]);
+	let client = client(ListingFileServer::from_path(root.path()).cors(CorsConfig::any_origin()));
+
+	for uri in ["/", "/file.txt"] {
+		let response = client
+			.get(uri)
+			.header(Header::new("Origin", "https://example.com"))
+			.dispatch();
+		assert_eq!(response.status(), Status::Ok, "{uri}");
+		assert_eq!(
+			response.headers().get_one("Access-Control-Allow-Origin"),
+			Some("*"),
+			"{uri}"
+		);
+	}
+
+	// Same-origin requests don't send an origin
+	let response = client.get("/file.txt").dispatch();
+	assert_eq!(
+		response.headers().get_one("Access-Control-Allow-Origin"),
+		None
+	);
+}
+
+#[test]
+fn options_requests_are_not_routed_without_a_policy() {
+	let root = root_with(&[]);
+	let client = client_for(root.path());
+
+	let response = client
+		.options("/")
+		.header(Header::new("Origin", "https://example.com"))
+		.dispatch();
+	assert_eq!(response.status(), Status::NotFound);
+	assert_eq!(
+		response.headers().get_one("Access-Control-Allow-Origin"),
+		None
+	);
+}
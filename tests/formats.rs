@@ -0,0 +1,212 @@
+//! The formats that directory listings are served in.
+
+// Modules
+mod common;
+
+// Uses
+use std::path::Path;
+
+use listing_file_server::{ListingFileServer, ListingFormat};
+use rocket::{
+	http::{Accept, ContentType, Header, Status},
+	local::blocking::Client,
+	serde::json::Value,
+};
+
+use crate::common::{client, client_for, root_with};
+
+#[test]
+fn listings_are_json_if_preferred() {
+	let root = root_with(&[("docs/", b""), ("readme.txt", b"read me")]);
+	let client = client(ListingFileServer::from_path(root.path()).enable_json(true));
+
+	let response = client.get("/").header(Accept::JSON).dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.content_type(), Some(ContentType::JSON));
+	let listing = response.into_json::<Value>().expect("the listing is JSON");
+	assert_eq!(listing["directory"], "/");
+	assert_eq!(listing["entries"][0]["name"], "docs/");
+	assert_eq!(listing["entries"][0]["is_dir"], true);
+	assert_eq!(listing["entries"][1]["name"], "readme.txt");
+	assert_eq!(listing["entries"][1]["size"], 7);
+
+	let response = client.get("/?format=json").dispatch();
+	assert_eq!(response.content_type(), Some(ContentType::JSON));
+
+	let response = client.get("/").header(Accept::HTML).dispatch();
+	assert_eq!(response.content_type(), Some(ContentType::HTML));
+	let response = client.get("/").dispatch();
+	assert_eq!(response.content_type(), Some(ContentType::HTML));
+}
+
+#[test]
+fn listings_are_not_json_unless_enabled() {
+	let root = root_with(&[("readme.txt", b"read me")]);
+	let client = client(ListingFileServer::from_path(root.path()));
+
+	for request in [
+		client.get("/").header(Accept::JSON),
+		client.get("/?format=json"),
+	] {
+		let response = request.dispatch();
+		assert_eq!(response.status(), Status::Ok);
+		assert_eq!(response.content_type(), Some(ContentType::HTML));
+	}
+}
+
+#[test]
+fn files_are_served_as_is_when_json_is_preferred() {
+	let root = root_with(&[("readme.txt", b"read me")]);
+	let client = client(ListingFileServer::from_path(root.path()).enable_json(true));
+
+	let response = client.get("/readme.txt").header(Accept::JSON).dispatch();
+	assert_eq!(response.content_type(), Some(ContentType::Plain));
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"read me"[..]));
+}
+
+/// Creates a server for `root` with every format but playlists enabled.
+fn all_formats_server(root: &Path) -> ListingFileServer {
+	ListingFileServer::from_path(root)
+		.enable_json(true)
+		.enable_text(true)
+		.enable_feed(true)
+}
+
+/// Gets the content type of the listing at `uri`, negotiated with the `Accept`
+/// header `accept` if there is one.
+fn negotiated_type(client: &Client, uri: &str, accept: Option<&str>) -> Option<String> {
+	let mut request = client.get(uri);
+	if let Some(accept) = accept {
+		request.add_header(Header::new("Accept", accept.to_owned()));
+	}
+	let response = request.dispatch();
+	assert_eq!(response.status(), Status::Ok, "{uri} {accept:?}");
+	response
+		.content_type()
+		.map(|content_type| format!("{}/{}", content_type.top(), content_type.sub()))
+}
+
+#[test]
+fn formats_are_negotiated_by_quality() {
+	let root = root_with(&[("file.txt", b"")]);
+	let client = client(all_formats_server(root.path()));
+
+	let cases = [
+		("text/html", "text/html"),
+		("application/json", "application/json"),
+		("text/plain", "text/plain"),
+		("application/atom+xml", "application/atom+xml"),
+		("application/json;q=0.5, text/html", "text/html"),
+		("text/html;q=0.5, application/json", "application/json"),
+		(
+			"text/html;q=0.1, text/plain;q=0.2, application/json;q=0.3",
+			"application/json",
+		),
+		// The most specific range sets the quality of each format
+		("text/*, text/html;q=0.5", "text/plain"),
+		("*/*;q=0.1, application/json;q=0.2", "application/json"),
+		// Formats with a quality of 0 aren't used
+		("application/json;q=0, */*", "text/html"),
+		// Unknown types fall back to the default
+		("image/png", "text/html"),
+	];
+	for (accept, media_type) in cases {
+		assert_eq!(
+			negotiated_type(&client, "/", Some(accept)).as_deref(),
+			Some(media_type),
+			"{accept}"
+		);
+	}
+}
+
+#[test]
+fn wildcards_and_no_preference_use_the_default_format() {
+	let root = root_with(&[("file.txt", b"")]);
+	let client =
+		client(all_formats_server(root.path()).default_listing_format(ListingFormat::Json));
+
+	for accept in [None, Some("*/*"), Some("text/html;q=0.5, */*")] {
+		assert_eq!(
+			negotiated_type(&client, "/", accept).as_deref(),
+			Some("application/json"),
+			"{accept:?}"
+		);
+	}
+	assert_eq!(
+		negotiated_type(&client, "/", Some("text/html")).as_deref(),
+		Some("text/html")
+	);
+}
+
+#[test]
+fn the_format_query_takes_precedence() {
+	let root = root_with(&[("file.txt", b"")]);
+	let client = client(all_formats_server(root.path()));
+
+	let cases = [
+		("/?format=html", "text/html"),
+		("/?format=json", "application/json"),
+		("/?format=text", "text/plain"),
+		("/?format=atom", "application/atom+xml"),
+		("/?feed=atom", "application/atom+xml"),
+	];
+	for (uri, media_type) in cases {
+		assert_eq!(
+			negotiated_type(&client, uri, Some("application/json, text/html;q=0.1")).as_deref(),
+			Some(media_type),
+			"{uri}"
+		);
+	}
+	// Formats that aren't enabled are ignored
+	assert_eq!(
+		negotiated_type(&client, "/?format=m3u", Some("text/plain")).as_deref(),
+		Some("text/plain")
+	);
+}
+
+/// Gets the names listed by the `Vary` headers of the response to `uri`.
+fn varies_by(client: &Client, uri: &str) -> Vec<String> {
+	let response = client
+		.get(uri)
+		.header(Header::new("Accept-Encoding", "gzip"))
+		.dispatch();
+	assert_eq!(response.status(), Status::Ok, "{uri}");
+	response
+		.headers()
+		.get("Vary")
+		.flat_map(|vary| vary.split(','))
+		.map(|name| name.trim().to_owned())
+		.collect()
+}
+
+#[test]
+fn listings_vary_by_accept_if_negotiated() {
+	let root = root_with(&[("file.txt", b"")]);
+
+	let html_only = client_for(root.path());
+	assert_eq!(varies_by(&html_only, "/"), Vec::<String>::new());
+
+	let negotiated = client(ListingFileServer::from_path(root.path()).enable_json(true));
+	assert_eq!(varies_by(&negotiated, "/"), ["Accept"]);
+	assert_eq!(varies_by(&negotiated, "/?format=json"), ["Accept"]);
+	// Files aren't negotiated
+	assert_eq!(varies_by(&negotiated, "/file.txt"), Vec::<String>::new());
+}
+
+#[test]
+fn responses_vary_by_accept_encoding_if_encoded() {
+	let root = root_with(&[("file.txt", b"")]);
+
+	let compressing = client(
+		ListingFileServer::from_path(root.path())
+			.enable_json(true)
+			.compression(true),
+	);
+	let mut listing_vary = varies_by(&compressing, "/");
+	listing_vary.sort_unstable();
+	assert_eq!(listing_vary, ["Accept", "Accept-Encoding"]);
+	assert_eq!(varies_by(&compressing, "/file.txt"), ["Accept-Encoding"]);
+
+	let precompressed = client(ListingFileServer::from_path(root.path()).precompressed(true));
+	assert_eq!(varies_by(&precompressed, "/file.txt"), ["Accept-Encoding"]);
+}
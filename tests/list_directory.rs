@@ -0,0 +1,116 @@
+//! Reading directories without a server.
+
+// Modules
+mod common;
+
+// Uses
+use std::{
+	fs::File,
+	time::{Duration, SystemTime},
+};
+
+use listing_file_server::{list_directory, ListingEntry, SortBy, SortOrder};
+use rocket::fs::Options;
+
+use crate::common::root_with;
+
+/// Gets the names of `entries`, in order.
+fn names(entries: &[ListingEntry]) -> Vec<&str> {
+	entries.iter().map(|entry| entry.name.as_str()).collect()
+}
+
+#[test]
+fn dotfiles_are_only_listed_if_enabled() {
+	let root = root_with(&[(".hidden", b""), ("shown", b""), (".config/", b"")]);
+
+	let entries = list_directory(
+		root.path(),
+		Options::None,
+		SortBy::Name,
+		SortOrder::Ascending,
+	)
+	.expect("the directory can be read");
+	assert_eq!(names(&entries), ["shown"]);
+
+	let entries = list_directory(
+		root.path(),
+		Options::DotFiles,
+		SortBy::Name,
+		SortOrder::Ascending,
+	)
+	.expect("the directory can be read");
+	assert_eq!(names(&entries), [".config", ".hidden", "shown"]);
+}
+
+#[test]
+fn entries_are_sorted_with_directories_first() {
+	// Sizes and modified times are both in the opposite order to the names
+	let root = root_with(&[
+		("a.txt", b"aaa"),
+		("b.txt", b"bb"),
+		("c.txt", b"c"),
+		("y/", b""),
+		("z/", b""),
+	]);
+	let epoch = SystemTime::UNIX_EPOCH;
+	for (name, age) in [("a.txt", 3), ("b.txt", 2), ("c.txt", 1)] {
+		File::options()
+			.write(true)
+			.open(root.path().join(name))
+			.and_then(|file| file.set_modified(epoch + Duration::from_secs(1000 - age)))
+			.expect("the modified time can be set");
+	}
+	for (name, age) in [("y", 2), ("z", 1)] {
+		File::open(root.path().join(name))
+			.and_then(|file| file.set_modified(epoch + Duration::from_secs(1000 - age)))
+			.expect("the modified time can be set");
+	}
+
+	let cases = [
+		(
+			SortBy::Name,
+			SortOrder::Ascending,
+			["y", "z", "a.txt", "b.txt", "c.txt"],
+		),
+		(
+			SortBy::Name,
+			SortOrder::Descending,
+			["z", "y", "c.txt", "b.txt", "a.txt"],
+		),
+		(
+			SortBy::Size,
+			SortOrder::Ascending,
+			["y", "z", "c.txt", "b.txt", "a.txt"],
+		),
+		(
+			SortBy::Size,
+			SortOrder::Descending,
+			["z", "y", "a.txt", "b.txt", "c.txt"],
+		),
+		(
+			SortBy::Modified,
+			SortOrder::Ascending,
+			["y", "z", "a.txt", "b.txt", "c.txt"],
+		),
+		(
+			SortBy::Modified,
+			SortOrder::Descending,
+			["z", "y", "c.txt", "b.txt", "a.txt"],
+		),
+	];
+	for (sort_by, sort_order, expected) in cases {
+		let entries = list_directory(root.path(), Options::None, sort_by, sort_order)
+			.expect("the directory can be read");
+		assert_eq!(names(&entries), expected, "{sort_by:?} {sort_order:?}");
+	}
+}
+
+#[test]
+fn missing_directories_are_errors() {
+	let root = root_with(&[("file.txt", b"")]);
+
+	for path in [root.path().join("missing"), root.path().join("file.txt")] {
+		let result = list_directory(&path, Options::None, SortBy::Name, SortOrder::Ascending);
+		assert!(result.is_err(), "{path:?}");
+	}
+}
@@ -0,0 +1,125 @@
+//! Listings of directories.
+
+// Modules
+mod common;
+
+// Uses
+use std::{fs, path::Path};
+
+use listing_file_server::ListingFileServer;
+use rocket::{fs::Options, http::Status, serde::json::Value};
+
+use crate::common::{client, client_at, root_with};
+
+/// Creates a server for `root` that removes its `gone` directory once the
+/// directory has been found, but before it's read.
+///
+/// The access filter is asked about index files in between, which is as close
+/// to the window between the two as a test can get.
+fn removing_server(root: &Path) -> ListingFileServer {
+	let gone = root.join("gone");
+	ListingFileServer::from_path(root)
+		.add_option(Options::Index)
+		.index_files(&["index.html"])
+		.access_filter(move |_, path| {
+			if path == Path::new("gone/index.html") {
+				fs::remove_dir_all(&gone).expect("the directory can be removed");
+				return false;
+			}
+			true
+		})
+}
+
+#[test]
+fn directories_removed_while_listing_are_not_found() {
+	let root = root_with(&[("gone/file.txt", b"")]);
+	let client = client(removing_server(root.path()));
+
+	let response = client.get("/gone/").dispatch();
+	assert_eq!(response.status(), Status::NotFound);
+	assert!(!root.path().join("gone").exists());
+}
+
+#[test]
+fn directories_removed_while_listing_use_the_vanished_status() {
+	let root = root_with(&[("gone/file.txt", b"")]);
+	let client = client(removing_server(root.path()).vanished_status(Status::Gone));
+
+	let response = client.get("/gone/").dispatch();
+	assert_eq!(response.status(), Status::Gone);
+}
+
+#[test]
+fn listings_link_within_the_mount_point() {
+	let root = root_with(&[("docs/read me.txt", b"read me")]);
+	let client = client_at(
+		"/files",
+		ListingFileServer::from_path(root.path()).enable_json(true),
+	);
+
+	let response = client.get("/files/docs/?format=json").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let listing = response.into_json::<Value>().expect("the listing is JSON");
+	assert_eq!(listing["directory"], "/docs/");
+	assert_eq!(listing["url"], "/files/docs/");
+	assert_eq!(listing["mount_base"], "/files");
+	assert_eq!(
+		listing["breadcrumbs"],
+		rocket::serde::json::json!([["/", "/files/"], ["docs", "/files/docs/"]])
+	);
+	assert_eq!(listing["entries"][0]["href"], "read%20me.txt");
+
+	let response = client.get("/files/docs/read%20me.txt").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"read me"[..]));
+
+	let response = client.get("/files/docs/").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let body = response.into_string().expect("the listing is UTF-8");
+	assert!(body.contains("href=\"/files/docs/\""));
+}
+
+#[test]
+fn listings_link_within_the_url_prefix() {
+	let root = root_with(&[("docs/file.txt", b"")]);
+	let client = client_at(
+		"/files",
+		ListingFileServer::from_path(root.path())
+			.enable_json(true)
+			.url_prefix("/downloads"),
+	);
+
+	let response = client.get("/files/docs/?format=json").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let listing = response.into_json::<Value>().expect("the listing is JSON");
+	assert_eq!(listing["url"], "/downloads/docs/");
+	assert_eq!(listing["mount_base"], "/files");
+	assert_eq!(listing["breadcrumbs"][0][1], "/downloads/");
+}
+
+#[test]
+fn empty_directories_are_listed() {
+	let root = root_with(&[("empty/", b""), ("full/file.txt", b"")]);
+	let client = client(
+		ListingFileServer::from_path(root.path())
+			.enable_json(true)
+			.show_parent_link(true),
+	);
+
+	let response = client.get("/empty/").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let body = response.into_string().expect("the listing is UTF-8");
+	assert!(body.contains("This directory is empty."));
+
+	let response = client.get("/empty/?format=json").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let listing = response.into_json::<Value>().expect("the listing is JSON");
+	assert_eq!(listing["empty"], true);
+	// Only the parent link is listed
+	assert_eq!(listing["entries"][0]["name"], "../");
+	assert_eq!(listing["entries"].as_array().map(Vec::len), Some(1));
+
+	let response = client.get("/full/?format=json").dispatch();
+	let listing = response.into_json::<Value>().expect("the listing is JSON");
+	assert_eq!(listing["empty"], false);
+}
@@ -0,0 +1,90 @@
+//! Entry and directory names that need special handling.
+#![cfg(unix)]
+
+// Modules
+mod common;
+
+// Uses
+use std::{ffi::OsStr, fs, os::unix::ffi::OsStrExt};
+
+use listing_file_server::ListingFileServer;
+use rocket::{http::Status, serde::json::Value};
+
+use crate::common::{client, client_for, root_with};
+
+#[test]
+fn non_utf8_entries_are_listed_lossily() {
+	let root = root_with(&[("good.txt", b"good")]);
+	fs::write(
+		root.path().join(OsStr::from_bytes(b"bad\xffname.txt")),
+		b"bad",
+	)
+	.expect("the file can be written");
+	let client = client_for(root.path());
+
+	let response = client.get("/").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let body = response.into_string().expect("the listing is UTF-8");
+	assert!(body.contains("good.txt"));
+	assert!(body.contains("bad\u{fffd}name.txt"));
+}
+
+#[test]
+fn non_utf8_entries_can_be_skipped() {
+	let root = root_with(&[("good.txt", b"good")]);
+	fs::write(
+		root.path().join(OsStr::from_bytes(b"bad\xffname.txt")),
+		b"bad",
+	)
+	.expect("the file can be written");
+	let client = client(ListingFileServer::from_path(root.path()).skip_non_utf8_names(true));
+
+	let response = client.get("/").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let body = response.into_string().expect("the listing is UTF-8");
+	assert!(body.contains("good.txt"));
+	assert!(!body.contains("name.txt"));
+}
+
+#[test]
+fn backslashes_are_kept_in_directory_names() {
+	let root = root_with(&[("back\\slash/file.txt", b"inside")]);
+	let client = client(ListingFileServer::from_path(root.path()).enable_json(true));
+
+	let response = client.get("/back%5Cslash/?format=json").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let listing = response.into_json::<Value>().expect("the listing is JSON");
+	assert_eq!(listing["directory"], "/back\\slash/");
+	assert_eq!(listing["segments"][0], "back\\slash");
+	assert_eq!(listing["url"], "/back%5Cslash/");
+
+	let response = client.get("/back%5Cslash/file.txt").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"inside"[..]));
+}
+
+#[test]
+fn non_utf8_directories_are_listed() {
+	let root = root_with(&[]);
+	let directory = root.path().join(OsStr::from_bytes(b"bad\xffdir"));
+	fs::create_dir(&directory).expect("the directory can be created");
+	fs::write(directory.join("file.txt"), b"inside").expect("the file can be written");
+	let client = client(ListingFileServer::from_path(root.path()).enable_json(true));
+
+	let response = client.get("/bad%FFdir/?format=json").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let listing = response.into_json::<Value>().expect("the listing is JSON");
+	assert_eq!(listing["directory"], "/bad\u{fffd}dir/");
+	assert_eq!(listing["entries"][0]["name"], "file.txt");
+
+	let response = client.get("/bad%FFdir/file.txt").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"inside"[..]));
+
+	// The directory's own listing still renders
+	let response = client.get("/bad%FFdir/").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert!(response
+		.into_string()
+		.is_some_and(|body| body.contains("file.txt")));
+}
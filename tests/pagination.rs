@@ -0,0 +1,94 @@
+//! Listings split into pages.
+
+// Modules
+mod common;
+
+// Uses
+use std::path::Path;
+
+use listing_file_server::{Listing, ListingFileServer};
+use rocket::{fs::Options, http::Status, local::blocking::Client};
+
+use crate::common::{client, root_with};
+
+/// Creates a server for `root` whose listings describe their page, followed
+/// by the names of their entries.
+fn page_server(root: &Path) -> ListingFileServer {
+	ListingFileServer::new(root, Options::None, |listing: Listing| {
+		let page = listing.pagination.map_or_else(String::new, |pagination| {
+			format!(
+				"page {} of {}, {} per page, {} in total",
+				pagination.page,
+				pagination.page_count,
+				pagination.per_page,
+				pagination.total_entries
+			)
+		});
+		let names = listing
+			.entries
+			.iter()
+			.map(|entry| entry.name.as_str())
+			.collect::<Vec<_>>();
+		format!("{page}: {}", names.join(" "))
+	})
+}
+
+/// Gets the listing at `uri`.
+fn listing(client: &Client, uri: &str) -> String {
+	let response = client.get(uri).dispatch();
+	assert_eq!(response.status(), Status::Ok, "{uri}");
+	response.into_string().expect("the listing is UTF-8")
+}
+
+/// Creates a directory of five files named `1` to `5`.
+fn numbered_root() -> tempfile::TempDir {
+	root_with(&[("1", b""), ("2", b""), ("3", b""), ("4", b""), ("5", b"")])
+}
+
+#[test]
+fn listings_are_paginated_by_the_page_size() {
+	let root = numbered_root();
+	let client = client(page_server(root.path()).page_size(2));
+
+	let pages = [
+		("/", "page 1 of 3, 2 per page, 5 in total: 1 2"),
+		("/?page=2", "page 2 of 3, 2 per page, 5 in total: 3 4"),
+		("/?page=3", "page 3 of 3, 2 per page, 5 in total: 5"),
+		("/?page=4", "page 4 of 3, 2 per page, 5 in total: "),
+		(
+			"/?page=2&per_page=3",
+			"page 2 of 2, 3 per page, 5 in total: 4 5",
+		),
+	];
+	for (uri, page) in pages {
+		assert_eq!(listing(&client, uri), page, "{uri}");
+	}
+}
+
+#[test]
+fn listings_are_only_paginated_if_asked() {
+	let root = numbered_root();
+	let client = client(page_server(root.path()));
+
+	assert_eq!(listing(&client, "/"), ": 1 2 3 4 5");
+	assert_eq!(listing(&client, "/?page=2"), ": 1 2 3 4 5");
+	assert_eq!(
+		listing(&client, "/?per_page=4&page=2"),
+		"page 2 of 2, 4 per page, 5 in total: 5"
+	);
+}
+
+#[test]
+fn listings_are_sorted_before_being_paginated() {
+	let root = numbered_root();
+	let client = client(page_server(root.path()).page_size(2));
+
+	assert_eq!(
+		listing(&client, "/?order=desc"),
+		"page 1 of 3, 2 per page, 5 in total: 5 4"
+	);
+	assert_eq!(
+		listing(&client, "/?order=desc&page=3"),
+		"page 3 of 3, 2 per page, 5 in total: 1"
+	);
+}
@@ -0,0 +1,78 @@
+//! Directories that the server isn't allowed to read.
+#![cfg(unix)]
+
+// Modules
+mod common;
+
+// Uses
+use std::{
+	fs::{self, Permissions},
+	os::unix::fs::PermissionsExt,
+};
+
+use listing_file_server::ListingFileServer;
+use rocket::{http::Status, serde::json::Value};
+
+use crate::common::{client, client_for, root_with};
+
+#[test]
+fn unreadable_directories_are_forbidden() {
+	let root = root_with(&[("locked/file.txt", b"locked"), ("open/", b"")]);
+	let locked = root.path().join("locked");
+	fs::set_permissions(&locked, Permissions::from_mode(0o000))
+		.expect("the permissions can be changed");
+	// Privileged users can read the directory regardless
+	if fs::read_dir(&locked).is_ok() {
+		fs::set_permissions(&locked, Permissions::from_mode(0o755))
+			.expect("the permissions can be restored");
+		return;
+	}
+	let client = client_for(root.path());
+
+	let locked_status = client.get("/locked/").dispatch().status();
+	let missing_status = client.get("/missing/").dispatch().status();
+	let open_status = client.get("/open/").dispatch().status();
+	// Restore the permissions so the directory can be cleaned up
+	fs::set_permissions(&locked, Permissions::from_mode(0o755))
+		.expect("the permissions can be restored");
+
+	assert_eq!(locked_status, Status::Forbidden);
+	assert_eq!(missing_status, Status::NotFound);
+	assert_eq!(open_status, Status::Ok);
+}
+
+#[test]
+fn unreadable_entries_are_marked_in_listings() {
+	let root = root_with(&[
+		("locked.txt", b""),
+		("open.txt", b""),
+		("unsearchable/file.txt", b""),
+		("open/", b""),
+	]);
+	let path = |name: &str| root.path().join(name);
+	fs::set_permissions(path("locked.txt"), Permissions::from_mode(0o200))
+		.expect("the permissions can be changed");
+	fs::set_permissions(path("unsearchable"), Permissions::from_mode(0o600))
+		.expect("the permissions can be changed");
+	// Privileged users can read the entries regardless
+	let privileged = fs::read(path("locked.txt")).is_ok();
+	let client = client(ListingFileServer::from_path(root.path()).enable_json(true));
+
+	let response = client.get("/?format=json").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	let listing = response.into_json::<Value>().expect("the listing is JSON");
+	// Restore the permissions so the directory can be cleaned up
+	fs::set_permissions(path("unsearchable"), Permissions::from_mode(0o755))
+		.expect("the permissions can be restored");
+
+	let readable = |name: &str| {
+		listing["entries"]
+			.as_array()
+			.and_then(|entries| entries.iter().find(|entry| entry["name"] == name))
+			.map(|entry| entry["is_readable"].clone())
+	};
+	assert_eq!(readable("open.txt"), Some(Value::Bool(true)));
+	assert_eq!(readable("open/"), Some(Value::Bool(true)));
+	assert_eq!(readable("locked.txt"), Some(Value::Bool(privileged)));
+	assert_eq!(readable("unsearchable/"), Some(Value::Bool(privileged)));
+}
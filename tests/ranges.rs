@@ -0,0 +1,170 @@
+//! Byte ranges of served files.
+
+// Modules
+mod common;
+
+// Uses
+use listing_file_server::ListingFileServer;
+use rocket::http::{Header, Status};
+
+use crate::common::{client, client_for, root_with};
+
+const CONTENTS: &[u8] = b"0123456789";
+
+#[test]
+fn single_range() {
+	let root = root_with(&[("digits.txt", CONTENTS)]);
+	let client = client_for(root.path());
+
+	let response = client
+		.get("/digits.txt")
+		.header(Header::new("Range", "bytes=2-5"))
+		.dispatch();
+	assert_eq!(response.status(), Status::PartialContent);
+	assert_eq!(
+		response.headers().get_one("Content-Range"),
+		Some("bytes 2-5/10")
+	);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"2345"[..]));
+}
+
+#[test]
+fn open_ended_range() {
+	let root = root_with(&[("digits.txt", CONTENTS)]);
+	let client = client_for(root.path());
+
+	let response = client
+		.get("/digits.txt")
+		.header(Header::new("Range", "bytes=7-"))
+		.dispatch();
+	assert_eq!(response.status(), Status::PartialContent);
+	assert_eq!(
+		response.headers().get_one("Content-Range"),
+		Some("bytes 7-9/10")
+	);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"789"[..]));
+}
+
+#[test]
+fn suffix_range() {
+	let root = root_with(&[("digits.txt", CONTENTS)]);
+	let client = client_for(root.path());
+
+	let response = client
+		.get("/digits.txt")
+		.header(Header::new("Range", "bytes=-3"))
+		.dispatch();
+	assert_eq!(response.status(), Status::PartialContent);
+	assert_eq!(
+		response.headers().get_one("Content-Range"),
+		Some("bytes 7-9/10")
+	);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"789"[..]));
+}
+
+#[test]
+fn unsatisfiable_range() {
+	let root = root_with(&[("digits.txt", CONTENTS)]);
+	let client = client_for(root.path());
+
+	let response = client
+		.get("/digits.txt")
+		.header(Header::new("Range", "bytes=10-"))
+		.dispatch();
+	assert_eq!(response.status(), Status::RangeNotSatisfiable);
+	assert_eq!(
+		response.headers().get_one("Content-Range"),
+		Some("bytes */10")
+	);
+}
+
+#[test]
+fn several_ranges_serve_the_whole_file() {
+	let root = root_with(&[("digits.txt", CONTENTS)]);
+	let client = client_for(root.path());
+
+	let response = client
+		.get("/digits.txt")
+		.header(Header::new("Range", "bytes=0-1,4-5"))
+		.dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.into_bytes().as_deref(), Some(CONTENTS));
+}
+
+#[test]
+fn if_range_matching_the_modified_time() {
+	let root = root_with(&[("digits.txt", CONTENTS)]);
+	let client = client_for(root.path());
+
+	let last_modified = client
+		.get("/digits.txt")
+		.dispatch()
+		.headers()
+		.get_one("Last-Modified")
+		.expect("files are served with their modified time")
+		.to_owned();
+	let response = client
+		.get("/digits.txt")
+		.header(Header::new("Range", "bytes=2-5"))
+		.header(Header::new("If-Range", last_modified))
+		.dispatch();
+	assert_eq!(response.status(), Status::PartialContent);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"2345"[..]));
+}
+
+#[test]
+fn if_range_not_matching_serves_the_whole_file() {
+	let root = root_with(&[("digits.txt", CONTENTS)]);
+	let client = client_for(root.path());
+
+	let response = client
+		.get("/digits.txt")
+		.header(Header::new("Range", "bytes=2-5"))
+		.header(Header::new("If-Range", "Thu, 01 Jan 1970 00:00:00 GMT"))
+		.dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.into_bytes().as_deref(), Some(CONTENTS));
+}
+
+#[test]
+fn if_range_with_an_entity_tag_serves_the_whole_file() {
+	let root = root_with(&[("digits.txt", CONTENTS)]);
+	let client = client_for(root.path());
+
+	let response = client
+		.get("/digits.txt")
+		.header(Header::new("Range", "bytes=2-5"))
+		.header(Header::new("If-Range", "\"digits\""))
+		.dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.into_bytes().as_deref(), Some(CONTENTS));
+}
+
+#[test]
+fn ranges_are_advertised_for_uncompressed_files() {
+	let root = root_with(&[("digits.txt", CONTENTS)]);
+	let client = client_for(root.path());
+
+	let response = client.get("/digits.txt").dispatch();
+	assert_eq!(response.headers().get_one("Accept-Ranges"), Some("bytes"));
+}
+
+#[test]
+fn ranges_are_not_advertised_for_compressed_files() {
+	let contents = "compressible text\n".repeat(256);
+	let root = root_with(&[("text.txt", contents.as_bytes())]);
+	let client = client(ListingFileServer::from_path(root.path()).compression(true));
+
+	let response = client
+		.get("/text.txt")
+		.header(Header::new("Accept-Encoding", "gzip"))
+		.dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+	assert_eq!(response.headers().get_one("Accept-Ranges"), None);
+
+	// Without gzip, the same file can still be resumed
+	let response = client.get("/text.txt").dispatch();
+	assert_eq!(response.headers().get_one("Content-Encoding"), None);
+	assert_eq!(response.headers().get_one("Accept-Ranges"), Some("bytes"));
+}
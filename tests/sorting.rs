@@ -0,0 +1,193 @@
+//! The order that directory listings are sorted in.
+
+// Modules
+mod common;
+
+// Uses
+use std::path::Path;
+
+use listing_file_server::{Listing, ListingFileServer, SortBy, SortOrder};
+use rocket::{fs::Options, http::Status, local::blocking::Client};
+
+use crate::common::{client, root_with};
+
+/// Creates a server for `root` whose listings are the names of their entries,
+/// one per line.
+fn names_server(root: &Path) -> ListingFileServer {
+	ListingFileServer::new(root, Options::None, |listing: Listing| {
+		listing
+			.entries
+			.iter()
+			.map(|entry| entry.name.as_str())
+			.collect::<Vec<_>>()
+			.join("\n")
+	})
+}
+
+/// Gets the names listed at `uri`, in order.
+fn listed_names(client: &Client, uri: &str) -> Vec<String> {
+	let response = client.get(uri).dispatch();
+	assert_eq!(response.status(), Status::Ok, "{uri}");
+	let body = response.into_string().expect("the listing is UTF-8");
+	body.lines().map(str::to_owned).collect()
+}
+
+/// Creates a directory whose files' sizes are in the opposite order to their
+/// names, alongside two subdirectories.
+fn sized_root() -> tempfile::TempDir {
+	root_with(&[
+		("apple.txt", b"aaa"),
+		("banana.txt", b"bb"),
+		("cherry.txt", b"c"),
+		("berry/", b""),
+		("date/", b""),
+	])
+}
+
+#[test]
+fn listings_are_sorted_by_name_with_directories_first_by_default() {
+	let root = sized_root();
+	let client = client(names_server(root.path()));
+
+	assert_eq!(
+		listed_names(&client, "/"),
+		["berry/", "date/", "apple.txt", "banana.txt", "cherry.txt"]
+	);
+}
+
+#[test]
+fn listings_are_sorted_by_the_configured_field_and_order() {
+	let root = sized_root();
+
+	let by_size = client(names_server(root.path()).sort(SortBy::Size, SortOrder::Ascending));
+	assert_eq!(
+		listed_names(&by_size, "/"),
+		["berry/", "date/", "cherry.txt", "banana.txt", "apple.txt"]
+	);
+
+	let by_name = client(names_server(root.path()).sort(SortBy::Name, SortOrder::Descending));
+	assert_eq!(
+		listed_names(&by_name, "/"),
+		["date/", "berry/", "cherry.txt", "banana.txt", "apple.txt"]
+	);
+}
+
+#[test]
+fn directories_are_sorted_among_files_if_not_first() {
+	let root = sized_root();
+	let client = client(names_server(root.path()).dirs_first(false));
+
+	assert_eq!(
+		listed_names(&client, "/"),
+		["apple.txt", "banana.txt", "berry/", "cherry.txt", "date/"]
+	);
+}
+
+#[test]
+fn listings_are_sorted_by_the_query() {
+	let root = sized_root();
+	let client = client(names_server(root.path()));
+
+	assert_eq!(
+		listed_names(&client, "/?sort=size&order=desc"),
+		["date/", "berry/", "apple.txt", "banana.txt", "cherry.txt"]
+	);
+	assert_eq!(
+		listed_names(&client, "/?sort=name&order=descending"),
+		["date/", "berry/", "cherry.txt", "banana.txt", "apple.txt"]
+	);
+	// An order without a field keeps the configured field
+	assert_eq!(
+		listed_names(&client, "/?order=desc"),
+		["date/", "berry/", "cherry.txt", "banana.txt", "apple.txt"]
+	);
+}
+
+#[test]
+fn invalid_sort_queries_are_ignored() {
+	let root = sized_root();
+	let client = client(names_server(root.path()).sort(SortBy::Size, SortOrder::Ascending));
+
+	let configured = ["berry/", "date/", "cherry.txt", "banana.txt", "apple.txt"];
+	for uri in ["/?sort=colour", "/?order=sideways", "/?sort=&order="] {
+		assert_eq!(listed_names(&client, uri), configured, "{uri}");
+	}
+	assert_eq!(
+		listed_names(&client, "/?sort=colour&order=desc"),
+		["date/", "berry/", "apple.txt", "banana.txt", "cherry.txt"]
+	);
+}
+
+#[test]
+fn the_active_sort_is_passed_to_the_renderer() {
+	let root = sized_root();
+	let client = client(ListingFileServer::new(
+		root.path(),
+		Options::None,
+		|listing: Listing| format!("{:?} {:?}", listing.sort_by, listing.sort_order),
+	));
+
+	let sorts = [
+		("/", "Name Ascending"),
+		("/?sort=modified", "Modified Ascending"),
+		("/?sort=size&order=desc", "Size Descending"),
+		("/?sort=colour&order=desc", "Name Descending"),
+	];
+	for (uri, sort) in sorts {
+		let response = client.get(uri).dispatch();
+		assert_eq!(response.into_string().as_deref(), Some(sort), "{uri}");
+	}
+}
+
+#[test]
+fn numbers_in_names_are_compared_by_value_if_natural() {
+	let root = root_with(&[
+		("file10.txt", b""),
+		("file2.txt", b""),
+		("file1.txt", b""),
+		("file01.txt", b""),
+		("file002.txt", b""),
+		("a01b", b""),
+		("a1c", b""),
+	]);
+
+	let natural = client(names_server(root.path()).natural_sort(true));
+	assert_eq!(
+		listed_names(&natural, "/"),
+		[
+			"a01b",
+			"a1c",
+			"file1.txt",
+			"file01.txt",
+			"file2.txt",
+			"file002.txt",
+			"file10.txt"
+		]
+	);
+	assert_eq!(
+		listed_names(&natural, "/?order=desc"),
+		[
+			"file10.txt",
+			"file002.txt",
+			"file2.txt",
+			"file01.txt",
+			"file1.txt",
+			"a1c",
+			"a01b"
+		]
+	);
+
+	let plain = client(names_server(root.path()));
+	assert_eq!(
+		listed_names(&plain, "/"),
+		[
+			"a01b",
+			"a1c",
+			"file002.txt",
+			"file01.txt",
+			"file1.txt",
+			"file10.txt",
+			"file2.txt"
+		]
+	);
+}
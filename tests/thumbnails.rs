@@ -0,0 +1,93 @@
+//! Thumbnails of images.
+#![cfg(feature = "image")]
+
+// Modules
+mod common;
+
+// Uses
+use std::{fs, io::Cursor, path::Path};
+
+use image::{ImageFormat, RgbImage};
+use listing_file_server::ListingFileServer;
+use rocket::{
+	config::LogLevel,
+	futures::future::join_all,
+	http::{ContentType, Status},
+	local::asynchronous::Client,
+	Config,
+};
+
+use crate::common::{client, root_with};
+
+/// Encodes a 64 by 32 pixel image as `format`.
+fn image_data(format: ImageFormat) -> Vec<u8> {
+	let mut data = Vec::new();
+	RgbImage::new(64, 32)
+		.write_to(&mut Cursor::new(&mut data), format)
+		.expect("the image can be encoded");
+	data
+}
+
+/// Creates a server for `root` with thumbnails enabled and cached in
+/// `cache_dir`.
+fn thumbnail_server(root: &Path, cache_dir: &Path) -> ListingFileServer {
+	ListingFileServer::from_path(root)
+		.enable_thumbnails(true)
+		.thumbnail_cache_dir(cache_dir)
+}
+
+#[test]
+fn thumbnails_fit_the_requested_size() {
+	let png = image_data(ImageFormat::Png);
+	let root = root_with(&[("image.png", &png)]);
+	let cache = root_with(&[]);
+	let client = client(thumbnail_server(root.path(), cache.path()));
+
+	let response = client.get("/image.png?thumb=16").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.content_type(), Some(ContentType::PNG));
+	let thumbnail = image::load_from_memory(&response.into_bytes().expect("there's a body"))
+		.expect("the thumbnail is an image");
+	assert_eq!((thumbnail.width(), thumbnail.height()), (16, 8));
+
+	// Without the parameter, the image itself is served
+	let response = client.get("/image.png").dispatch();
+	assert_eq!(response.into_bytes(), Some(png));
+}
+
+#[rocket::async_test]
+async fn concurrent_thumbnails_are_cached_whole() {
+	let root = root_with(&[("image.png", &image_data(ImageFormat::Png))]);
+	let cache = root_with(&[]);
+	let rocket = rocket::custom(Config {
+		log_level: LogLevel::Off,
+		..Config::debug_default()
+	})
+	.mount("/", thumbnail_server(root.path(), cache.path()));
+	let client = Client::untracked(rocket)
+		.await
+		.expect("the server launches");
+
+	let requests = (0..8).map(|_| async {
+		client
+			.get("/image.png?thumb=16")
+			.dispatch()
+			.await
+			.into_bytes()
+			.await
+			.expect("there's a body")
+	});
+	let thumbnails = join_all(requests).await;
+	assert!(thumbnails.windows(2).all(|pair| pair[0] == pair[1]));
+
+	// Only the finished thumbnail is left, whichever request wrote it
+	let cached = fs::read_dir(cache.path())
+		.expect("the cache directory exists")
+		.map(|entry| entry.expect("the entry can be read").path())
+		.collect::<Vec<_>>();
+	assert_eq!(cached.len(), 1, "{cached:?}");
+	assert_eq!(
+		fs::read(&cached[0]).expect("the thumbnail is cached"),
+		thumbnails[0]
+	);
+}
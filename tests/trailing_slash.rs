@@ -0,0 +1,142 @@
+//! Redirects that add or strip trailing slashes.
+
+// Modules
+mod common;
+
+// Uses
+use listing_file_server::{ListingFileServer, TrailingSlash};
+use rocket::{
+	fs::Options,
+	http::{Status, StatusClass},
+};
+
+use crate::common::{client, client_for, root_with};
+
+#[test]
+fn files_with_a_trailing_slash_are_redirected() {
+	let root = root_with(&[("readme.txt", b"read me"), ("docs/guide.txt", b"guide")]);
+	let client = client_for(root.path());
+
+	let response = client.get("/readme.txt/").dispatch();
+	assert_eq!(response.status(), Status::MovedPermanently);
+	assert_eq!(response.headers().get_one("Location"), Some("/readme.txt"));
+
+	let response = client.get("/docs/guide.txt/?download=1").dispatch();
+	assert_eq!(response.status(), Status::MovedPermanently);
+	assert_eq!(
+		response.headers().get_one("Location"),
+		Some("/docs/guide.txt?download=1")
+	);
+
+	let response = client.get("/readme.txt").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"read me"[..]));
+}
+
+#[test]
+fn files_with_a_trailing_slash_are_not_redirected_if_disabled() {
+	let root = root_with(&[("readme.txt", b"read me")]);
+	let client =
+		client(ListingFileServer::from_path(root.path()).trailing_slash(TrailingSlash::None));
+
+	let response = client.get("/readme.txt/").dispatch();
+	assert_ne!(response.status(), Status::MovedPermanently);
+	assert_eq!(response.headers().get_one("Location"), None);
+}
+
+#[test]
+fn directory_redirects_keep_the_query() {
+	let root = root_with(&[("docs/file.txt", b"")]);
+	let client =
+		client(ListingFileServer::from_path(root.path()).add_option(Options::NormalizeDirs));
+
+	let response = client.get("/docs?a=b").dispatch();
+	assert_eq!(response.status().class(), StatusClass::Redirection);
+	assert_eq!(response.headers().get_one("Location"), Some("/docs/?a=b"));
+
+	let response = client.get("/docs?sort=size&order=desc").dispatch();
+	assert_eq!(
+		response.headers().get_one("Location"),
+		Some("/docs/?sort=size&order=desc")
+	);
+
+	let response = client.get("/docs").dispatch();
+	assert_eq!(response.headers().get_one("Location"), Some("/docs/"));
+
+	let response = client.get("/docs/?a=b").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+}
+
+#[test]
+fn directories_get_a_trailing_slash_with_add_to_dirs() {
+	let root = root_with(&[("docs/guide.txt", b"guide")]);
+	let client =
+		client(ListingFileServer::from_path(root.path()).trailing_slash(TrailingSlash::AddToDirs));
+
+	let response = client.get("/docs?a=b").dispatch();
+	assert_eq!(response.status(), Status::MovedPermanently);
+	assert_eq!(response.headers().get_one("Location"), Some("/docs/?a=b"));
+
+	let response = client.get("/docs/guide.txt/").dispatch();
+	assert_eq!(response.status(), Status::MovedPermanently);
+	assert_eq!(
+		response.headers().get_one("Location"),
+		Some("/docs/guide.txt")
+	);
+
+	let response = client.get("/docs/").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+}
+
+#[test]
+fn every_trailing_slash_is_stripped_with_strip_from_all() {
+	let root = root_with(&[("docs/guide.txt", b"guide")]);
+	let client = client(
+		ListingFileServer::from_path(root.path())
+			.trailing_slash(TrailingSlash::StripFromAll)
+			.add_option(Options::NormalizeDirs),
+	);
+
+	let response = client.get("/docs/?a=b").dispatch();
+	assert_eq!(response.status(), Status::MovedPermanently);
+	assert_eq!(response.headers().get_one("Location"), Some("/docs?a=b"));
+
+	let response = client.get("/docs/guide.txt/").dispatch();
+	assert_eq!(response.status(), Status::MovedPermanently);
+	assert_eq!(
+		response.headers().get_one("Location"),
+		Some("/docs/guide.txt")
+	);
+
+	let response = client.get("/docs").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	// The root keeps its slash, since that's where the server is mounted
+	let response = client.get("/").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+}
+
+#[test]
+fn policy_redirects_are_temporary_if_configured() {
+	let root = root_with(&[("docs/guide.txt", b"guide")]);
+	let server = ListingFileServer::from_path(root.path()).normalize_redirect_permanent(false);
+
+	let add_to_dirs = client(server.clone().trailing_slash(TrailingSlash::AddToDirs));
+	let response = add_to_dirs.get("/docs").dispatch();
+	assert_eq!(response.status(), Status::TemporaryRedirect);
+
+	let strip_from_all = client(server.trailing_slash(TrailingSlash::StripFromAll));
+	for uri in ["/docs/", "/docs/guide.txt/"] {
+		let response = strip_from_all.get(uri).dispatch();
+		assert_eq!(response.status(), Status::TemporaryRedirect, "{uri}");
+	}
+}
+
+#[test]
+fn normalized_directories_are_redirected_like_file_server() {
+	let root = root_with(&[("docs/", b"")]);
+	let client =
+		client(ListingFileServer::from_path(root.path()).add_option(Options::NormalizeDirs));
+
+	let response = client.get("/docs").dispatch();
+	assert_eq!(response.status(), Status::PermanentRedirect);
+}
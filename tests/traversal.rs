@@ -0,0 +1,111 @@
+//! Requests that try to reach files outside of the server root.
+
+// Modules
+mod common;
+
+// Uses
+use std::fs;
+
+use listing_file_server::ListingFileServer;
+use rocket::http::Status;
+
+use crate::common::{client, client_for, root_with};
+
+const SECRET: &[u8] = b"the secret";
+
+/// The requests that would reach `secret.txt`, beside the root, if traversal
+/// weren't prevented.
+const TRAVERSALS: &[&str] = &[
+	"/../secret.txt",
+	"/../../etc/passwd",
+	"/public/../../secret.txt",
+	"/%2e%2e/secret.txt",
+	"/%2E%2E/secret.txt",
+	"/..%2fsecret.txt",
+	"/%2e%2e%2fsecret.txt",
+	"/public/%2e%2e%2f%2e%2e%2fsecret.txt",
+	"/..%5csecret.txt",
+	"/%2fetc%2fpasswd",
+	"/%00/../secret.txt",
+];
+
+/// Checks that `uri` doesn't serve the secret, or anything from outside the
+/// root.
+fn assert_refused(client: &rocket::local::blocking::Client, uri: &str) {
+	let response = client.get(uri).dispatch();
+	let status = response.status();
+	let body = response.into_bytes().unwrap_or_default();
+	assert!(
+		status == Status::NotFound || status == Status::BadRequest,
+		"{uri} was answered with {status}"
+	);
+	assert!(
+		!body.windows(SECRET.len()).any(|window| window == SECRET),
+		"{uri} served the secret"
+	);
+	assert!(
+		!body.windows(5).any(|window| window == b"root:"),
+		"{uri} served /etc/passwd"
+	);
+}
+
+#[test]
+fn traversal_is_refused() {
+	let dir = root_with(&[("secret.txt", SECRET), ("root/public/file.txt", b"public")]);
+	let client = client_for(&dir.path().join("root"));
+
+	for uri in TRAVERSALS {
+		assert_refused(&client, uri);
+	}
+	// The root itself is still served
+	let response = client.get("/public/file.txt").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.into_bytes().as_deref(), Some(&b"public"[..]));
+}
+
+#[test]
+fn traversal_is_refused_while_following_symlinks() {
+	let dir = root_with(&[("secret.txt", SECRET), ("root/public/file.txt", b"public")]);
+	let client =
+		client(ListingFileServer::from_path(dir.path().join("root")).follow_symlinks(true));
+
+	for uri in TRAVERSALS {
+		assert_refused(&client, uri);
+	}
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_out_of_the_root_are_only_followed_if_enabled() {
+	use std::os::unix::fs::symlink;
+
+	let dir = root_with(&[("secret.txt", SECRET), ("root/", b"")]);
+	let root = dir.path().join("root");
+	symlink(dir.path().join("secret.txt"), root.join("link.txt")).expect("the link can be made");
+
+	let contained = client(ListingFileServer::from_path(&root).follow_symlinks(false));
+	assert_refused(&contained, "/link.txt");
+
+	let following = client(ListingFileServer::from_path(&root).follow_symlinks(true));
+	let response = following.get("/link.txt").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	assert_eq!(response.into_bytes().as_deref(), Some(SECRET));
+}
+
+#[cfg(unix)]
+#[test]
+fn roots_reached_through_a_symlink_are_still_contained() {
+	use std::os::unix::fs::symlink;
+
+	let dir = root_with(&[("secret.txt", SECRET), ("real/file.txt", b"public")]);
+	let root = dir.path().join("root");
+	symlink(dir.path().join("real"), &root).expect("the link can be made");
+	fs::metadata(&root).expect("the linked root exists");
+
+	let client = client(ListingFileServer::from_path(&root).follow_symlinks(false));
+	let response = client.get("/file.txt").dispatch();
+	assert_eq!(response.status(), Status::Ok);
+	for uri in TRAVERSALS {
+		assert_refused(&client, uri);
+	}
+}
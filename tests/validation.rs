@@ -0,0 +1,94 @@
+//! Checking that servers are able to serve before they're mounted.
+
+// Modules
+mod common;
+
+// Uses
+use std::{fs, io};
+
+use listing_file_server::{Listing, ListingFileServer};
+use rocket::{fs::Options, http::Status, local::blocking::Client};
+
+use crate::common::root_with;
+
+/// Creates a client with nothing mounted, for making requests to validate
+/// servers with.
+fn request_client() -> Client {
+	Client::untracked(rocket::build()).expect("the server launches")
+}
+
+#[test]
+fn readable_roots_are_valid() {
+	let root = root_with(&[("file.txt", b"")]);
+	let server = ListingFileServer::from_path(root.path());
+	let client = request_client();
+
+	assert!(server.validate(None).is_ok());
+	assert!(server.validate(Some(&client.get("/"))).is_ok());
+}
+
+#[test]
+fn missing_roots_are_invalid() {
+	let root = root_with(&[("gone/", b"")]);
+	let gone = root.path().join("gone");
+	let server = ListingFileServer::from_path(&gone);
+	fs::remove_dir(&gone).expect("the directory can be removed");
+
+	let error = server.validate(None).expect_err("the root is missing");
+	assert_eq!(error.kind(), io::ErrorKind::NotFound);
+}
+
+#[cfg(unix)]
+#[test]
+fn unreadable_roots_are_invalid() {
+	use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+
+	let root = root_with(&[("locked/", b"")]);
+	let locked = root.path().join("locked");
+	let server = ListingFileServer::from_path(&locked);
+	fs::set_permissions(&locked, Permissions::from_mode(0o000))
+		.expect("the permissions can be changed");
+	// Privileged users can read the directory regardless
+	let privileged = fs::read_dir(&locked).is_ok();
+	let result = server.validate(None);
+	// Restore the permissions so the directory can be cleaned up
+	fs::set_permissions(&locked, Permissions::from_mode(0o755))
+		.expect("the permissions can be restored");
+
+	if !privileged {
+		let error = result.expect_err("the root is unreadable");
+		assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+	}
+}
+
+#[test]
+fn failing_renderers_are_invalid() {
+	let root = root_with(&[]);
+	let server = ListingFileServer::new_fallible(root.path(), Options::None, |_| {
+		Err::<String, _>(Status::InternalServerError)
+	});
+	let client = request_client();
+
+	// The renderer is only called with a request
+	assert!(server.validate(None).is_ok());
+	let error = server
+		.validate(Some(&client.get("/")))
+		.expect_err("the renderer fails");
+	assert_eq!(error.kind(), io::ErrorKind::Other);
+	assert!(error.to_string().contains("500"), "{error}");
+}
+
+#[test]
+fn panicking_renderers_are_invalid() {
+	let root = root_with(&[]);
+	let server = ListingFileServer::new(root.path(), Options::None, |_: Listing| -> String {
+		panic!("the renderer is broken")
+	});
+	let client = request_client();
+
+	let error = server
+		.validate(Some(&client.get("/")))
+		.expect_err("the renderer panics");
+	assert_eq!(error.kind(), io::ErrorKind::Other);
+	assert!(error.to_string().contains("panicked"), "{error}");
+}